@@ -0,0 +1,247 @@
+use crate::config::Config;
+use crate::logs::*;
+use crate::utils::quote;
+use hyprland::data::{Bind, Binds};
+use hyprland::keyword::Keyword;
+use hyprland::prelude::*;
+use hyprland::Result;
+use std::env;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::sync::OnceLock;
+
+static SOCKET: OnceLock<Option<String>> = OnceLock::new();
+
+const BIND_MARKER: &str = "--bind";
+
+type Taken = (u16, String, String);
+
+const MODIFIERS: [(&str, u16); 13] = [
+    ("SHIFT", 1 << 0),
+    ("CAPS", 1 << 1),
+    ("CTRL", 1 << 2),
+    ("CONTROL", 1 << 2),
+    ("ALT", 1 << 3),
+    ("MOD1", 1 << 3),
+    ("MOD2", 1 << 4),
+    ("MOD3", 1 << 5),
+    ("SUPER", 1 << 6),
+    ("WIN", 1 << 6),
+    ("LOGO", 1 << 6),
+    ("MOD4", 1 << 6),
+    ("MOD5", 1 << 7),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KeyBind {
+    modmask: u16,
+    key: String,
+    action: String,
+}
+
+impl KeyBind {
+    fn parse(bind: &str) -> Option<KeyBind> {
+        let parts = bind.split(',').map(str::trim).collect::<Vec<_>>();
+        let (mods, key, action) = match parts[..] {
+            [mods, key] if !key.is_empty() => (mods, key, "toggle"),
+            [mods, key, action @ ("toggle" | "show" | "hide")] if !key.is_empty() => {
+                (mods, key, action)
+            }
+            _ => {
+                let _ = log(format!("Invalid bind '{bind}'"), Warn);
+                return None;
+            }
+        };
+
+        Some(KeyBind {
+            modmask: modmask(mods),
+            key: key.into(),
+            action: action.into(),
+        })
+    }
+
+    fn combo(&self) -> String {
+        format!("{}, {}", mods_of(self.modmask), self.key)
+    }
+
+    fn matches(&self, (modmask, key, _): &Taken) -> bool {
+        *modmask == self.modmask && key.eq_ignore_ascii_case(&self.key)
+    }
+}
+
+fn modmask(mods: &str) -> u16 {
+    let mods = mods.to_uppercase();
+    let tokens: Vec<&str> = mods.split(['_', '+', ' ']).collect();
+    MODIFIERS
+        .iter()
+        .filter(|(name, _)| tokens.contains(name))
+        .fold(0, |mask, (_, bit)| mask | bit)
+}
+
+fn mods_of(mask: u16) -> String {
+    let (mut names, mut seen) = (vec![], 0);
+    for (name, bit) in MODIFIERS {
+        if mask & bit != 0 && seen & bit == 0 {
+            names.push(name);
+            seen |= bit;
+        }
+    }
+    names.join(" ")
+}
+
+fn executable() -> String {
+    env::current_exe()
+        .map(|exe| quote(&exe.to_string_lossy()))
+        .unwrap_or_else(|_| "hyprscratch".into())
+}
+
+fn program_name(command: &str) -> Option<&OsStr> {
+    let program = match command.strip_prefix('\'') {
+        Some(rest) => rest.split('\'').next()?,
+        None => command.split_whitespace().next()?,
+    };
+    Path::new(program).file_name()
+}
+
+fn is_registered(bind: &Bind, exe: &str) -> bool {
+    bind.dispatcher == "exec"
+        && bind.arg.split_whitespace().any(|a| a == BIND_MARKER)
+        && program_name(&bind.arg) == program_name(exe)
+}
+
+fn bind_command(exe: &str, action: &str, name: &str) -> String {
+    match SOCKET.get().cloned().flatten() {
+        Some(socket) => format!(
+            "{exe} {action} {name} {BIND_MARKER} --socket {}",
+            quote(&socket)
+        ),
+        None => format!("{exe} {action} {name} {BIND_MARKER}"),
+    }
+}
+
+pub fn init_binds(config: &Config, socket: Option<&str>) -> Result<()> {
+    let _ = SOCKET.set(socket.map(str::to_string));
+    register_binds(config)
+}
+
+pub fn register_binds(config: &Config) -> Result<()> {
+    let exe = executable();
+    let (stale, existing): (Vec<Bind>, Vec<Bind>) = Binds::get()?
+        .into_iter()
+        .partition(|b| is_registered(b, &exe));
+
+    for bind in stale {
+        let combo = format!("{}, {}", mods_of(bind.modmask), bind.key);
+        Keyword::set("unbind", combo)?;
+    }
+
+    let mut taken: Vec<Taken> = existing
+        .into_iter()
+        .filter(|b| b.submap.is_empty())
+        .map(|b| (b.modmask, b.key, format!("{} {}", b.dispatcher, b.arg)))
+        .collect();
+
+    for name in &config.names {
+        let sc = &config.scratchpads[name];
+        if !sc.binds.is_empty() && sc.is_parameterized() {
+            let msg = format!("Scratchpad '{name}' takes arguments and cannot be bound");
+            log(msg, Warn)?;
+            continue;
+        }
+
+        for bind in sc.binds.iter().filter_map(|b| KeyBind::parse(b)) {
+            if let Some((_, _, target)) = taken.iter().find(|t| bind.matches(t)) {
+                let combo = bind.combo();
+                let msg = format!("Bind '{combo}' of '{name}' is already bound to '{target}'");
+                log(msg, Warn)?;
+                continue;
+            }
+
+            let command = bind_command(&exe, &bind.action, name);
+            Keyword::set("bind", format!("{}, exec, {command}", bind.combo()))?;
+            taken.push((bind.modmask, bind.key, format!("exec {command}")));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modmask() {
+        assert_eq!(modmask("SUPER"), 64);
+        assert_eq!(modmask("SUPER SHIFT"), 65);
+        assert_eq!(modmask("ctrl_alt"), 12);
+        assert_eq!(modmask("CONTROL"), 4);
+        assert_eq!(modmask("SUPER+ALT"), 72);
+        assert_eq!(modmask("SUPERSHIFT"), 0);
+        assert_eq!(modmask("MOD12"), 0);
+        assert_eq!(modmask(""), 0);
+
+        assert_eq!(mods_of(65), "SHIFT SUPER");
+        assert_eq!(mods_of(12), "CTRL ALT");
+        assert_eq!(mods_of(0), "");
+    }
+
+    #[test]
+    fn test_parse_bind() {
+        assert_eq!(
+            KeyBind::parse("SUPER SHIFT, t"),
+            Some(KeyBind {
+                modmask: 65,
+                key: "t".into(),
+                action: "toggle".into(),
+            })
+        );
+        assert_eq!(
+            KeyBind::parse(" SUPER , Return , show "),
+            Some(KeyBind {
+                modmask: 64,
+                key: "Return".into(),
+                action: "show".into(),
+            })
+        );
+        assert_eq!(KeyBind::parse("SUPER, t, exec"), None);
+        assert_eq!(KeyBind::parse("SUPER"), None);
+        assert_eq!(KeyBind::parse("SUPER, "), None);
+    }
+
+    #[test]
+    fn test_is_registered() {
+        let bind = |dispatcher: &str, arg: &str| Bind {
+            locked: false,
+            mouse: false,
+            release: false,
+            repeat: false,
+            modmask: 64,
+            submap: String::new(),
+            key: "t".into(),
+            keycode: 0,
+            dispatcher: dispatcher.into(),
+            arg: arg.into(),
+        };
+
+        let exe = "'/usr/local/bin/hyprscratch'";
+        let moved = "'/home/me/.cargo/bin/hyprscratch' toggle term --bind";
+        assert!(is_registered(&bind("exec", moved), exe));
+        assert!(is_registered(
+            &bind(
+                "exec",
+                "'/opt/my apps/hyprscratch' show term --bind -s '/tmp/s'"
+            ),
+            exe
+        ));
+        assert!(!is_registered(
+            &bind("exec", "hyprscratch toggle term"),
+            exe
+        ));
+        assert!(!is_registered(
+            &bind("exec", "'/usr/bin/kitty' --bind"),
+            exe
+        ));
+        assert!(!is_registered(&bind("killactive", ""), exe));
+        assert_eq!(program_name(moved), program_name(exe));
+    }
+}
@@ -1,23 +1,82 @@
 use crate::logs::*;
-use crate::scratchpad::{Scratchpad, ScratchpadOptions};
-use crate::utils::{dequote, get_flag_arg};
+use crate::scratchpad::{Hooks, Scratchpad, ScratchpadOptions, Step};
+use crate::utils::{check_pattern, dequote, get_flag_arg, instance_key, instance_workspace};
 use crate::DEFAULT_CONFIG_FILES;
 use crate::KNOWN_COMMANDS;
 use hyprland::Result;
 use std::collections::HashMap;
 use std::env;
 use std::ffi::OsStr;
-use std::fs::File;
+use std::fs::{create_dir_all, File, OpenOptions};
 use std::io::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::SystemTime;
 
 type Scratchpads = HashMap<String, Scratchpad>;
-type Groups = HashMap<String, Vec<Scratchpad>>;
+type Groups = HashMap<String, Group>;
+type Profiles = HashMap<String, Profile>;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Group {
+    pub names: Vec<String>,
+    pub scratchpads: Vec<Scratchpad>,
+    pub groups: Vec<String>,
+    pub options: String,
+    pub rules: String,
+    pub exclusive: bool,
+}
+
+impl Group {
+    fn push(&mut self, name: &str, scratchpad: &Scratchpad) {
+        self.names.push(name.into());
+        self.scratchpads.push(scratchpad.clone());
+    }
+
+    fn append(&mut self, other: &mut Group) {
+        self.names.append(&mut other.names);
+        self.scratchpads.append(&mut other.scratchpads);
+        self.groups.append(&mut other.groups);
+        self.options = format!("{} {}", self.options, other.options).trim().into();
+        self.rules = join_rules(&self.rules, &other.rules);
+        self.exclusive |= other.exclusive;
+    }
+
+    fn add_options(&mut self, options: &str) {
+        self.options = format!("{} {options}", self.options).trim().into();
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Profile {
+    pub names: Vec<String>,
+    pub daemon_options: String,
+    pub close: bool,
+}
+
+impl Profile {
+    fn append(&mut self, other: &mut Profile) {
+        self.names.append(&mut other.names);
+        self.daemon_options = format!("{} {}", self.daemon_options, other.daemon_options)
+            .trim()
+            .into();
+        self.close |= other.close;
+    }
+}
+
+fn join_rules(rules: &str, new_rules: &str) -> String {
+    match (rules.is_empty(), new_rules.is_empty()) {
+        (_, true) => rules.into(),
+        (true, false) => new_rules.into(),
+        (false, false) => format!("{rules}; {new_rules}"),
+    }
+}
 
 struct ConfigData {
     daemon_options: String,
     scratchpads: Scratchpads,
     groups: Groups,
+    profiles: Profiles,
     names: Vec<String>,
 }
 
@@ -27,6 +86,7 @@ impl ConfigData {
             daemon_options: String::new(),
             scratchpads: HashMap::new(),
             groups: HashMap::new(),
+            profiles: HashMap::new(),
             names: Vec::new(),
         }
     }
@@ -35,6 +95,7 @@ impl ConfigData {
         self.daemon_options.push_str(&new_data.daemon_options);
         self.scratchpads.extend(new_data.scratchpads.drain());
         self.groups.extend(new_data.groups.drain());
+        self.profiles.extend(new_data.profiles.drain());
         self.names.append(&mut new_data.names);
     }
 
@@ -43,21 +104,97 @@ impl ConfigData {
         self.scratchpads.insert(name.into(), scratchpad.clone());
     }
 
-    fn add_to_group(&mut self, name: &str, scratchpads: &mut Vec<Scratchpad>) {
-        if let Some(group) = self.groups.get_mut(name) {
-            group.append(scratchpads);
-        } else {
-            self.groups.insert(name.into(), scratchpads.clone());
-        }
+    fn add_to_group(&mut self, name: &str, group: &mut Group) {
+        self.groups.entry(name.into()).or_default().append(group);
+    }
+
+    fn add_profile(&mut self, name: &str, profile: &mut Profile) {
+        self.profiles
+            .entry(name.into())
+            .or_default()
+            .append(profile);
     }
 
     fn add_to_config(&mut self, args: &[String]) {
         if let Some([title, command, rules, opts]) = parse_args(args) {
             let scratchpad = Scratchpad::new(&title, &command, &rules, &opts);
+            if !has_distinct_instances(&title, &scratchpad) {
+                return;
+            }
             self.add_scratchpad(&title, &scratchpad);
 
             if let Some(g) = get_flag_arg(args, "group") {
-                self.add_to_group(&g, &mut vec![scratchpad]);
+                let mut group = Group::default();
+                group.push(&title, &scratchpad);
+                self.add_to_group(&g, &mut group);
+            }
+        }
+    }
+
+    fn get_members(&self, name: &str, visited: &mut Vec<String>) -> Vec<String> {
+        if visited.iter().any(|v| v == name) {
+            let _ = log(format!("Group '{name}' contains itself"), Warn);
+            return vec![];
+        }
+
+        let group = match self.groups.get(name) {
+            Some(group) => group,
+            None => {
+                let _ = log(format!("Nested group '{name}' not found"), Warn);
+                return vec![];
+            }
+        };
+
+        visited.push(name.into());
+        let mut members = group.names.clone();
+        for nested in &group.groups {
+            for member in self.get_members(nested, visited) {
+                if !members.contains(&member) {
+                    members.push(member);
+                }
+            }
+        }
+        visited.pop();
+        members
+    }
+
+    fn resolve_groups(&mut self) {
+        let mut group_names: Vec<String> = self.groups.keys().cloned().collect();
+        group_names.sort();
+        let members: Vec<Vec<String>> = group_names
+            .iter()
+            .map(|name| self.get_members(name, &mut vec![]))
+            .collect();
+
+        for (name, members) in group_names.iter().zip(&members) {
+            let group = &self.groups[name];
+            for member in members {
+                if let Some(sc) = self.scratchpads.get_mut(member) {
+                    sc.add_opts(&group.options);
+                    sc.add_rules(&group.rules);
+                }
+            }
+        }
+
+        for (name, members) in group_names.iter().zip(members) {
+            let group = self.groups.get_mut(name).unwrap_log(file!(), line!());
+            group.scratchpads = members
+                .iter()
+                .filter_map(|m| self.scratchpads.get(m).cloned())
+                .collect();
+            group.names = members;
+        }
+    }
+
+    fn check_profiles(&self) {
+        for (profile, data) in &self.profiles {
+            let missing = data.names.iter().filter(|n| match n.split_once(':') {
+                Some(("group", group)) => !self.groups.contains_key(group),
+                _ => !self.scratchpads.contains_key(*n),
+            });
+
+            for name in missing {
+                let _ = log(format!("'{name}' in profile '{profile}' not found"), Warn);
             }
         }
     }
@@ -76,7 +213,10 @@ struct ParserState {
     active_scratchpad: Option<String>,
     scratchpad_data: HashMap<String, String>,
     active_group: Option<String>,
-    group_data: Vec<Scratchpad>,
+    group_data: Group,
+    active_profile: Option<String>,
+    profile_data: Profile,
+    steps: Vec<Step>,
     in_scope: bool,
 }
 
@@ -86,7 +226,10 @@ impl ParserState {
             active_scratchpad: None,
             scratchpad_data: HashMap::new(),
             active_group: None,
-            group_data: vec![],
+            group_data: Group::default(),
+            active_profile: None,
+            profile_data: Profile::default(),
+            steps: vec![],
             in_scope: false,
         };
 
@@ -109,10 +252,26 @@ impl ParserState {
             } else {
                 self.in_scope = true;
                 self.active_scratchpad = Some(n.into());
-                let scratchpad_fields = ["title", "class", "command", "rules", "options"];
+                let scratchpad_fields = [
+                    "title",
+                    "class",
+                    "command",
+                    "rules",
+                    "options",
+                    "when",
+                    "on_show",
+                    "on_hide",
+                    "on_spawn",
+                    "on_close",
+                    "env",
+                    "cwd",
+                    "workspace",
+                    "bind",
+                ];
                 for f in scratchpad_fields {
                     self.scratchpad_data.insert(f.into(), String::new());
                 }
+                self.steps.clear();
             }
         }
     }
@@ -122,11 +281,26 @@ impl ParserState {
         let e = line.find(' ');
         if let (Some(s), Some(e)) = (s, e) {
             self.active_group = Some(line[s + 1..e].into());
+            self.group_data = Group::default();
         } else {
             warn_syntax_err(Nameless);
         }
     }
 
+    fn open_profile(&mut self, line: &str) {
+        match line
+            .split_whitespace()
+            .next()
+            .and_then(|p| p.split_once(':'))
+        {
+            Some((_, name)) if !name.is_empty() && name != "{" => {
+                self.active_profile = Some(name.into());
+                self.profile_data = Profile::default();
+            }
+            _ => warn_syntax_err(Nameless),
+        }
+    }
+
     fn validate_data(&mut self) -> bool {
         let warn_empty = |fields: &[&str]| -> bool {
             if fields.iter().all(|&f| self.scratchpad_data[f].is_empty()) {
@@ -164,7 +338,26 @@ impl ParserState {
             )
         };
         let options = &self.scratchpad_data["options"];
-        Scratchpad::new(title, &command, &rules, options)
+        let mut scratchpad = Scratchpad::new(title, &command, &rules, options);
+        scratchpad.add_conditions(&self.scratchpad_data["when"]);
+        scratchpad.hooks = Hooks {
+            on_show: self.scratchpad_data["on_show"].clone(),
+            on_hide: self.scratchpad_data["on_hide"].clone(),
+            on_spawn: self.scratchpad_data["on_spawn"].clone(),
+            on_close: self.scratchpad_data["on_close"].clone(),
+        };
+        scratchpad.add_env(&self.scratchpad_data["env"]);
+        scratchpad.set_workspace(&self.scratchpad_data["workspace"]);
+        if !self.scratchpad_data["cwd"].is_empty() {
+            scratchpad.cwd = Some(self.scratchpad_data["cwd"].clone());
+        }
+        scratchpad.steps = std::mem::take(&mut self.steps);
+        scratchpad.binds = self.scratchpad_data["bind"]
+            .lines()
+            .map(|b| b.trim().to_string())
+            .filter(|b| !b.is_empty())
+            .collect();
+        scratchpad
     }
 
     fn append_to_field(&mut self, k: &str, v: &str) {
@@ -179,17 +372,33 @@ impl ParserState {
             "command" => "?",
             "rules" => ";",
             "options" => "",
+            "when" => ",",
+            "on_show" | "on_hide" | "on_spawn" | "on_close" => ";",
+            "env" | "bind" => "\n",
             _ => return,
         };
 
         field.push_str(&format!("{sep} {v}"));
     }
 
+    fn set_step(&mut self, k: &str, v: &str) {
+        let index = self.scratchpad_data["command"].split('?').count() - 1;
+        if self.steps.len() <= index {
+            self.steps.resize(index + 1, Step::default());
+        }
+        self.steps[index].set(k, v);
+    }
+
+    fn close_profile(&mut self, config_data: &mut ConfigData) {
+        if let Some(name) = self.active_profile.take() {
+            config_data.add_profile(&name, &mut self.profile_data);
+        }
+    }
+
     fn close_group(&mut self, config_data: &mut ConfigData) {
         if let Some(name) = &self.active_group {
             config_data.add_to_group(name, &mut self.group_data);
             self.active_group = None;
-            self.group_data = vec![];
         } else {
             warn_syntax_err(Unopened);
         }
@@ -221,7 +430,7 @@ impl ConfigCache {
             scratchpads
                 .iter()
                 .filter(|(_, sc)| cond(&sc.options))
-                .map(|(name, sc)| (sc.title.clone(), name.clone()))
+                .map(|(name, sc)| (sc.title.clone(), sc.hide_workspace(name)))
                 .collect::<HashMap<_, _>>()
         };
 
@@ -236,7 +445,9 @@ impl ConfigCache {
         }
     }
 
-    fn update_cache(&mut self, options: &ScratchpadOptions, name: &str, title: &str) {
+    fn update_cache(&mut self, name: &str, sc: &Scratchpad) {
+        let (options, title) = (&sc.options, &sc.title);
+        let workspace = sc.hide_workspace(name);
         if options.ephemeral {
             self.ephemeral_titles.push(title.into());
         }
@@ -245,26 +456,53 @@ impl ConfigCache {
         }
         if !options.special {
             self.normal_titles.push(title.into());
+            self.normal_map.insert(title.into(), workspace.clone());
         }
         if !options.special && !options.persist {
-            self.replace_map.insert(title.into(), name.into());
+            self.replace_map.insert(title.into(), workspace.clone());
         }
         if !options.pin && !options.sticky {
-            self.clean_map.insert(title.into(), name.into());
+            self.clean_map.insert(title.into(), workspace.clone());
         }
         if !options.shiny && !options.sticky && !options.pin {
-            self.spotless_map.insert(title.into(), name.into());
+            self.spotless_map.insert(title.into(), workspace.clone());
         }
     }
 }
 
+fn own_writes() -> MutexGuard<'static, HashMap<PathBuf, SystemTime>> {
+    static OWN_WRITES: OnceLock<Mutex<HashMap<PathBuf, SystemTime>>> = OnceLock::new();
+    OWN_WRITES
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+pub fn is_own_write(path: &Path) -> bool {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified());
+    match (own_writes().get(path), modified) {
+        (Some(written), Ok(modified)) => *written == modified,
+        _ => false,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instance {
+    pub template: String,
+    pub args: HashMap<String, String>,
+    pub scratchpad: Scratchpad,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Config {
     pub daemon_options: String,
     pub config_file: String,
     pub scratchpads: Scratchpads,
     pub groups: Groups,
+    pub profiles: Profiles,
     pub names: Vec<String>,
+    pub instances: HashMap<String, Instance>,
+    pub attached: Scratchpads,
     pub cache: ConfigCache,
 }
 
@@ -287,25 +525,176 @@ impl Config {
             config_file: config_files[0].clone(),
             scratchpads: config_data.scratchpads,
             groups: config_data.groups,
+            profiles: config_data.profiles,
             names: config_data.names,
+            instances: HashMap::new(),
+            attached: HashMap::new(),
         })
     }
 
+    pub fn get_profile_members(&self, profile: &Profile) -> Vec<String> {
+        let mut members = vec![];
+        for name in &profile.names {
+            match name.split_once(':') {
+                Some(("group", group)) => {
+                    if let Some(group) = self.groups.get(group) {
+                        members.extend(group.names.iter().cloned());
+                    }
+                }
+                _ => members.push(name.clone()),
+            }
+        }
+        members
+    }
+
     pub fn add_scratchpad(&mut self, name: &str, sc: &Scratchpad) {
         if self.scratchpads.contains_key(name) {
             return;
         }
 
-        self.cache.update_cache(&sc.options, name, &sc.title);
+        self.cache.update_cache(name, sc);
         self.scratchpads.insert(name.into(), sc.clone());
         self.names.push(name.into());
     }
 
+    pub fn attach(&mut self, name: &str, sc: &Scratchpad) {
+        self.add_scratchpad(name, sc);
+        self.attached.insert(name.into(), sc.clone());
+    }
+
+    pub fn add_instance(
+        &mut self,
+        template: &str,
+        args: &HashMap<String, String>,
+    ) -> std::result::Result<String, String> {
+        let mut scratchpad = match self.scratchpads.get(template) {
+            Some(sc) => sc.instantiate(args)?,
+            None => return Err(template.into()),
+        };
+
+        let key = instance_key(template, args);
+        if scratchpad.workspace.is_none() {
+            scratchpad.workspace = Some(instance_workspace(&key));
+        }
+
+        if !self.instances.contains_key(&key) {
+            self.cache.update_cache(&key, &scratchpad);
+            let instance = Instance {
+                template: template.into(),
+                args: args.clone(),
+                scratchpad,
+            };
+            self.instances.insert(key.clone(), instance);
+        }
+        Ok(key)
+    }
+
+    pub fn remove_instance(&mut self, key: &str) -> Option<Instance> {
+        let instance = self.instances.remove(key)?;
+        self.rebuild_cache();
+        Some(instance)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Scratchpad> {
+        self.scratchpads
+            .get(name)
+            .or_else(|| self.instances.get(name).map(|i| &i.scratchpad))
+    }
+
+    pub fn all_scratchpads(&self) -> impl Iterator<Item = (&String, &Scratchpad)> {
+        let instances = self.instances.iter().map(|(k, i)| (k, &i.scratchpad));
+        self.scratchpads.iter().chain(instances)
+    }
+
+    fn rebuild_cache(&mut self) {
+        self.cache = ConfigCache::new(&self.scratchpads);
+        for (key, instance) in &self.instances {
+            self.cache.update_cache(key, &instance.scratchpad);
+        }
+    }
+
+    pub fn remove_scratchpad(&mut self, name: &str) -> Option<Scratchpad> {
+        let sc = self.scratchpads.remove(name)?;
+        self.attached.remove(name);
+        self.names.retain(|n| n != name);
+        self.rebuild_cache();
+        Some(sc)
+    }
+
+    fn get_dedicated_file(&self) -> String {
+        let is_dedicated = |f: &String| {
+            !f.contains("hyprland.conf") && !f.ends_with(".lua") && !f.ends_with(".txt")
+        };
+        if is_dedicated(&self.config_file) {
+            return self.config_file.clone();
+        }
+
+        find_config_files()
+            .into_iter()
+            .find(is_dedicated)
+            .unwrap_or_else(|| {
+                let home = env::var("HOME").unwrap_log(file!(), line!());
+                format!("{home}/.config/{}", DEFAULT_CONFIG_FILES[0])
+            })
+    }
+
+    pub fn save_scratchpad(&self, name: &str, sc: &Scratchpad) -> Result<()> {
+        let path = self.get_dedicated_file();
+        if let Some(parent) = Path::new(&path).parent() {
+            create_dir_all(parent)?;
+        }
+
+        let mut block = format!("\n{name} {{\n    class = {}\n", sc.title);
+        if !sc.command.is_empty() {
+            block += &format!("    command = {}\n", sc.command);
+        }
+        if !sc.options.as_str().is_empty() {
+            block += &format!("    options = {}\n", sc.options.as_str());
+        }
+        block += "}\n";
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        file.write_all(block.as_bytes())?;
+        if let Ok(modified) = file.metadata().and_then(|m| m.modified()) {
+            own_writes().insert(PathBuf::from(&path), modified);
+        }
+        log(format!("Scratchpad '{name}' saved to {path}"), Info)
+    }
+
+    pub fn get_name(&self, title: &str, class: &str) -> Option<String> {
+        let instance = || {
+            self.instances
+                .iter()
+                .find(|(_, i)| i.scratchpad.matches(title, class))
+                .map(|(key, _)| key)
+        };
+
+        self.names
+            .iter()
+            .find(|n| self.scratchpads[*n].matches(title, class))
+            .or_else(instance)
+            .cloned()
+    }
+
     pub fn reload(&mut self, config_path: Option<String>) -> Result<()> {
-        *self = match config_path {
+        let config = match config_path {
             Some(_) => Config::new(config_path)?,
             None => Config::new(Some(self.config_file.clone()))?,
         };
+        let old = std::mem::replace(self, config);
+
+        for (name, sc) in &old.attached {
+            if !self.scratchpads.contains_key(name) {
+                self.attach(name, sc);
+            }
+        }
+
+        for instance in old.instances.values() {
+            let template = self.scratchpads.get(&instance.template);
+            if template.is_some_and(Scratchpad::is_parameterized) {
+                let _ = self.add_instance(&instance.template, &instance.args);
+            }
+        }
         Ok(())
     }
 
@@ -353,7 +742,7 @@ impl Config {
     }
 
     fn format_groups(&self) -> String {
-        type GroupField<'a> = &'a dyn Fn((&String, &Vec<Scratchpad>)) -> String;
+        type GroupField<'a> = &'a dyn Fn((&String, &Group)) -> String;
         let format_group = |field: GroupField| {
             self.groups
                 .iter()
@@ -362,18 +751,18 @@ impl Config {
                 .join("\u{2C02}")
         };
 
-        let get_titles = |scratchpads: &Vec<Scratchpad>| {
-            scratchpads
-                .iter()
-                .map(|sc| sc.title.clone())
-                .collect::<Vec<String>>()
-                .join(",")
+        let get_options = |group: &Group| {
+            let mode = if group.exclusive { "exclusive " } else { "" };
+            format!("{mode}{}", group.options).trim().into()
         };
 
         format!(
-            "\u{2C00}{}\u{2C01}{}",
+            "\u{2C00}{}\u{2C01}{}\u{2C01}{}\u{2C01}{}\u{2C01}{}",
             format_group(&|(x, _)| x.clone()),
-            format_group(&|(_, x)| get_titles(x))
+            format_group(&|(_, x)| x.names.join(",")),
+            format_group(&|(_, x)| x.groups.join(",")),
+            format_group(&|(_, x)| get_options(x)),
+            format_group(&|(_, x)| x.rules.clone()),
         )
     }
 
@@ -438,6 +827,9 @@ fn get_config_data(config_files: &[String]) -> Result<ConfigData> {
         config_data.append(&mut new_data);
     }
 
+    config_data.resolve_groups();
+    config_data.check_profiles();
+
     Ok(config_data)
 }
 
@@ -516,12 +908,21 @@ fn get_lines_with(pat: &str, config: &str, is_lua: bool) -> Vec<String> {
 }
 
 fn warn_unknown_options(opts: &str) {
-    let known_arg_options = ["monitor", "group"];
+    let known_arg_options = [
+        "monitor",
+        "group",
+        "fullscreen",
+        "only_on_workspace",
+        "only_on_monitor",
+    ];
     let known_options = [
         "",
         "pin",
         "cover",
         "persist",
+        "per_workspace",
+        "inherit_cwd",
+        "respawn",
         "sticky",
         "shiny",
         "lazy",
@@ -548,6 +949,13 @@ fn warn_unknown_options(opts: &str) {
 
     opts.split_whitespace()
         .fold(false, |acc, x| warn_unknown(x, acc));
+
+    let opts = opts.split_whitespace().collect::<Vec<_>>();
+    for pair in opts.windows(2) {
+        if let ["only_on_workspace" | "only_on_monitor", pattern] = pair {
+            check_pattern(pattern);
+        }
+    }
 }
 
 fn extract_rules(cmd: &str) -> [String; 2] {
@@ -635,6 +1043,8 @@ use SyntaxErr::*;
 enum SyntaxErr<'a> {
     MissingField(&'a [&'a str], &'a str),
     UnknownField(&'a str),
+    UnknownGroupField(&'a str),
+    UnknownProfileField(&'a str),
     NameOutsideGroup,
     GlobalInScope,
     NotInScope,
@@ -647,6 +1057,8 @@ fn warn_syntax_err(err: SyntaxErr) {
     let msg = match err {
         MissingField(f, n) => &format!("Field '{}' not found for scratchpad '{n}'", f.join(" or ")),
         UnknownField(f) => &format!("Unknown scratchpad field '{f}'"),
+        UnknownGroupField(f) => &format!("Unknown group field '{f}'"),
+        UnknownProfileField(f) => &format!("Unknown profile field '{f}'"),
         NameOutsideGroup => "Name defined outside of a group scope",
         GlobalInScope => "Global variable defined inside scratchpad",
         NotInScope => "Field set outside of scratchpad",
@@ -660,13 +1072,29 @@ fn warn_syntax_err(err: SyntaxErr) {
 fn open_scope(line: &str, state: &mut ParserState) {
     if line.starts_with("group:") {
         state.open_group(line);
+    } else if line.starts_with("profile:") {
+        state.open_profile(line);
     } else {
         state.open_scratchpad(line);
     }
 }
 
+fn has_distinct_instances(name: &str, sc: &Scratchpad) -> bool {
+    match sc.untitled_args().first() {
+        Some(arg) => {
+            let msg = format!("Title of scratchpad '{name}' must use argument '{arg}' to tell its instances apart");
+            let _ = log(msg, Warn);
+            false
+        }
+        None => true,
+    }
+}
+
 fn close_scope(state: &mut ParserState, config_data: &mut ConfigData) {
-    if !state.in_scope {
+    if !state.in_scope && state.active_profile.is_some() {
+        state.close_profile(config_data);
+        return;
+    } else if !state.in_scope {
         state.close_group(config_data);
         return;
     }
@@ -677,13 +1105,15 @@ fn close_scope(state: &mut ParserState, config_data: &mut ConfigData) {
     }
 
     let scratchpad = state.create_scratchpad();
-    if let Some(name) = &state.active_scratchpad {
-        config_data.add_scratchpad(name, &scratchpad);
-        state.active_scratchpad = None;
-    }
+    if let Some(name) = state.active_scratchpad.take() {
+        if !has_distinct_instances(&name, &scratchpad) {
+            return;
+        }
+        config_data.add_scratchpad(&name, &scratchpad);
 
-    if state.active_group.is_some() {
-        state.group_data.push(scratchpad);
+        if state.active_group.is_some() {
+            state.group_data.push(&name, &scratchpad);
+        }
     }
 }
 
@@ -693,8 +1123,10 @@ fn add_copy_to_group(name: &str, config_data: &mut ConfigData, state: &mut Parse
         return;
     }
 
-    if let Some(sc) = config_data.scratchpads.get(name) {
-        state.group_data.push(sc.clone());
+    if let Some(("group", nested)) = name.split_once(':') {
+        state.group_data.groups.push(nested.into());
+    } else if let Some(sc) = config_data.scratchpads.get(name) {
+        state.group_data.push(name, sc);
     }
 }
 
@@ -721,18 +1153,69 @@ fn set_field<'a>(state: &mut ParserState, (k, v): (&'a str, &'a str)) {
         return;
     }
 
-    if state.scratchpad_data.contains_key(k) {
+    if k == "after" || k == "delay" {
+        state.set_step(k, v);
+    } else if state.scratchpad_data.contains_key(k) {
         state.append_to_field(k, v);
     } else {
         warn_syntax_err(UnknownField(k));
     }
 }
 
+fn set_group_field(state: &mut ParserState, (k, v): (&str, &str)) {
+    let group = &mut state.group_data;
+    match (k, v) {
+        ("options", _) => {
+            warn_unknown_options(v);
+            group.add_options(v);
+        }
+        ("monitor", _) => group.add_options(&format!("monitor {v}")),
+        ("rules", _) => group.rules = join_rules(&group.rules, &v.replace(',', ";")),
+        ("mode", "exclusive") => state.group_data.exclusive = true,
+        ("mode", "all") => state.group_data.exclusive = false,
+        ("mode", _) => {
+            let _ = log(format!("Unknown group mode '{v}'"), Warn);
+        }
+        _ => warn_syntax_err(UnknownGroupField(k)),
+    }
+}
+
+fn profile_options(options: &str) -> String {
+    let (kept, ignored): (Vec<&str>, Vec<&str>) = options
+        .split_whitespace()
+        .partition(|o| matches!(o.trim_start_matches('-'), "clean" | "spotless"));
+
+    if !ignored.is_empty() {
+        let msg = format!(
+            "Profiles can only set clean and spotless, ignoring '{}'",
+            ignored.join(" ")
+        );
+        let _ = log(msg, Warn);
+    }
+    kept.join(" ")
+}
+
+fn set_profile_field(state: &mut ParserState, (k, v): (&str, &str)) {
+    let profile = &mut state.profile_data;
+    match (k, v) {
+        ("name", _) => profile.names.push(v.into()),
+        ("daemon_options", _) => profile.daemon_options = profile_options(v),
+        ("mode", "close") => profile.close = true,
+        ("mode", "hide") => profile.close = false,
+        ("mode", _) => {
+            let _ = log(format!("Unknown profile mode '{v}'"), Warn);
+        }
+        _ => warn_syntax_err(UnknownProfileField(k)),
+    }
+}
+
 fn set_var<'a>((k, v): (&'a str, &'a str), config_data: &mut ConfigData, state: &mut ParserState) {
     let (k, v) = (k.trim(), escape(v));
     match k {
+        _ if !state.in_scope && state.active_profile.is_some() => set_profile_field(state, (k, &v)),
         "global_options" | "global_rules" | "daemon_options" => set_global(state, (k, v)),
         "name" => add_copy_to_group(&v, config_data, state),
+        _ if !state.in_scope && state.active_group.is_some() => set_group_field(state, (k, &v)),
         _ => set_field(state, (k, &v)),
     }
 }
@@ -762,7 +1245,9 @@ fn parse_hyprlang(config: &str) -> Result<ConfigData> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::expand_workspace;
     use pretty_assertions::assert_eq;
+    use std::time::Duration;
     use std::{fs::File, vec};
 
     fn expected_scratchpads(mode: bool) -> Scratchpads {
@@ -808,20 +1293,19 @@ mod tests {
 
     fn expected_groups() -> Groups {
         let scs = expected_scratchpads(false);
-        let mut groups = HashMap::new();
-        groups.insert(
-            "one".into(),
-            vec![scs["nautilus"].clone(), scs["noname"].clone()],
-        );
-        groups.insert(
-            "two".into(),
-            vec![scs["btop"].clone(), scs["wierd"].clone()],
-        );
-        groups.insert(
-            "three".into(),
-            vec![scs["btop"].clone(), scs["noname"].clone()],
-        );
-        groups
+        let group = |names: [&str; 2], exclusive| Group {
+            names: names.map(String::from).to_vec(),
+            scratchpads: names.map(|n| scs[n].clone()).to_vec(),
+            exclusive,
+            ..Default::default()
+        };
+
+        HashMap::from([
+            ("one".into(), group(["nautilus", "noname"], false)),
+            ("two".into(), group(["btop", "wierd"], false)),
+            ("three".into(), group(["btop", "noname"], false)),
+            ("four".into(), group(["nautilus", "wierd"], true)),
+        ])
     }
 
     fn open_conf(config_file: &str) -> String {
@@ -845,6 +1329,272 @@ mod tests {
         assert_eq!(config_data.groups, expected_groups());
     }
 
+    #[test]
+    fn test_group_options() {
+        let config = "
+            one {
+                title = one
+                command = one
+                options = pin
+            }
+
+            two {
+                title = two
+                command = two
+            }
+
+            group:inner {
+                name = two
+                options = persist
+            }
+
+            group:outer {
+                name = one
+                name = group:inner
+                monitor = DP-1
+                rules = size 10 10
+            }
+
+            group:loop {
+                name = group:loop
+            }
+        ";
+
+        let mut config_data = parse_hyprlang(config).unwrap();
+        config_data.resolve_groups();
+
+        let outer = &config_data.groups["outer"];
+        assert_eq!(outer.names, ["one", "two"]);
+        assert_eq!(outer.groups, ["inner"]);
+        assert_eq!(outer.scratchpads[1], config_data.scratchpads["two"]);
+        assert!(config_data.groups["loop"].names.is_empty());
+
+        let [one, two] = ["one", "two"].map(|n| &config_data.scratchpads[n]);
+        assert_eq!(one.options.as_str(), "pin monitor DP-1");
+        assert_eq!(two.options.as_str(), "persist monitor DP-1");
+        assert_eq!(two.command, "[size 10 10] two");
+    }
+
+    #[test]
+    fn test_hooks() {
+        let config = "
+            player {
+                title = player
+                command = player
+                on_hide = playerctl pause
+                on_hide = notify-send hidden
+                on_close = pkill -RTMIN+8 waybar
+            }
+        ";
+
+        let config_data = parse_hyprlang(config).unwrap();
+        let hooks = &config_data.scratchpads["player"].hooks;
+        assert_eq!(hooks.on_hide, "playerctl pause; notify-send hidden");
+        assert_eq!(hooks.on_close, "pkill -RTMIN+8 waybar");
+        assert!(hooks.on_show.is_empty());
+    }
+
+    #[test]
+    fn test_save_scratchpad() {
+        let file =
+            env::temp_dir().join(format!("hyprscratch_test_save_{}.conf", std::process::id()));
+        let path = file.to_str().unwrap();
+
+        let mut config = Config::new(Some("test_configs/test_config3.txt".into())).unwrap();
+        config.config_file = path.into();
+        let chat = Scratchpad::new("discord", "discord --start-minimized", "", "persist sticky");
+        config.save_scratchpad("chat", &chat).unwrap();
+
+        let content = std::fs::read_to_string(path).unwrap();
+        let saved = &parse_hyprlang(&content).unwrap().scratchpads["chat"];
+        assert_eq!(saved, &chat);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_profiles() {
+        let config = "
+            chat {
+                class = discord
+                command = discord
+            }
+
+            group:work {
+                mail {
+                    class = thunderbird
+                    command = thunderbird
+                }
+            }
+
+            profile:work {
+                name = group:work
+                name = chat
+                daemon_options = clean spotless
+                mode = close
+            }
+
+            profile:personal {
+                name = chat
+                daemon_options = spotless eager --layout grid
+            }
+        ";
+
+        let config_data = parse_hyprlang(config).unwrap();
+        assert_eq!(
+            config_data.profiles["work"],
+            Profile {
+                names: vec!["group:work".into(), "chat".into()],
+                daemon_options: "clean spotless".into(),
+                close: true,
+            }
+        );
+        assert_eq!(config_data.profiles["personal"].names, vec!["chat"]);
+        assert_eq!(config_data.profiles["personal"].daemon_options, "spotless");
+        assert!(!config_data.profiles["personal"].close);
+        assert!(config_data.daemon_options.is_empty());
+        assert_eq!(config_data.groups["work"].names, vec!["mail"]);
+    }
+
+    #[test]
+    fn test_shared_workspace() {
+        let config = "
+            chat {
+                class = discord
+                command = discord
+                options = special
+                workspace = special:comms
+            }
+
+            mail {
+                class = thunderbird
+                command = thunderbird
+                options = per_workspace
+                workspace = comms
+            }
+        ";
+
+        let config_data = parse_hyprlang(config).unwrap();
+        let (chat, mail) = (
+            &config_data.scratchpads["chat"],
+            &config_data.scratchpads["mail"],
+        );
+        assert_eq!(chat.workspace.as_deref(), Some("comms"));
+        assert_eq!(chat.hide_workspace("chat"), "comms");
+        assert_eq!(mail.hide_workspace("mail"), "comms-{workspace}");
+    }
+
+    #[test]
+    fn test_env_and_cwd() {
+        let config = "
+            editor {
+                title = editor
+                command = kitty nvim
+                env = EDITOR=nvim
+                env = GREETING = hello world
+                cwd = ~/notes
+            }
+        ";
+
+        let config_data = parse_hyprlang(config).unwrap();
+        let editor = &config_data.scratchpads["editor"];
+        assert_eq!(
+            editor.env,
+            vec![
+                ("EDITOR".to_string(), "nvim".to_string()),
+                ("GREETING".to_string(), "hello world".to_string()),
+            ]
+        );
+        assert_eq!(editor.cwd.as_deref(), Some("~/notes"));
+    }
+
+    #[test]
+    fn test_binds() {
+        let config = "
+            terminal {
+                title = terminal
+                command = kitty --title terminal
+                bind = SUPER, t
+                bind = SUPER SHIFT, t, hide
+            }
+        ";
+
+        let config_data = parse_hyprlang(config).unwrap();
+        assert_eq!(
+            config_data.scratchpads["terminal"].binds,
+            vec!["SUPER, t", "SUPER SHIFT, t, hide"]
+        );
+    }
+
+    #[test]
+    fn test_own_write() {
+        let file =
+            env::temp_dir().join(format!("hyprscratch_own_write_{}.conf", std::process::id()));
+        let path = file.to_str().unwrap();
+        std::fs::write(path, "term {\n    title = term\n    command = kitty\n}\n").unwrap();
+
+        let config = Config::new(Some(path.into())).unwrap();
+        let chat = Scratchpad::new("chat_class", "", "", "");
+        config.save_scratchpad("chat", &chat).unwrap();
+        assert!(is_own_write(Path::new(path)));
+
+        let file = OpenOptions::new().append(true).open(path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        assert!(!is_own_write(Path::new(path)));
+        assert!(!is_own_write(Path::new("/tmp/hyprscratch_missing.conf")));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_instance_titles() {
+        let config = "
+            notes {
+                title = notes {arg:file}
+                command = kitty --title 'notes {arg:file}' nvim {arg:file}
+            }
+
+            logs {
+                title = logs
+                command = kitty --title logs tail -f {arg:file}
+            }
+        ";
+
+        let config_data = parse_hyprlang(config).unwrap();
+        assert_eq!(config_data.names, ["notes"]);
+        assert!(!config_data.scratchpads.contains_key("logs"));
+    }
+
+    #[test]
+    fn test_steps() {
+        let config = "
+            notebook {
+                title = notebook
+                command = jupyter lab --no-browser
+                command = firefox localhost:8888
+                after = ~/.local/share/jupyter/runtime
+                delay = 1.5s
+                command = kitty --title notebook
+                delay = 300
+            }
+        ";
+
+        let config_data = parse_hyprlang(config).unwrap();
+        let notebook = &config_data.scratchpads["notebook"];
+        assert_eq!(
+            notebook.steps,
+            vec![
+                Step::default(),
+                Step {
+                    after: Some("~/.local/share/jupyter/runtime".into()),
+                    delay: Duration::from_millis(1500),
+                },
+                Step {
+                    after: None,
+                    delay: Duration::from_millis(300),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_config() {
         let config_data = parse_config(
@@ -894,6 +1644,28 @@ mod tests {
         assert_eq!(config_data.scratchpads, expected_scratchpads);
     }
 
+    #[test]
+    fn test_per_workspace_cache() {
+        let scratchpads = HashMap::from([
+            (
+                "notes".to_string(),
+                Scratchpad::new("notes", "noop", "", "per_workspace"),
+            ),
+            ("btop".to_string(), Scratchpad::new("btop", "noop", "", "")),
+        ]);
+
+        let mut cache = ConfigCache::new(&scratchpads);
+        cache.update_cache(
+            "files",
+            &Scratchpad::new("files", "noop", "", "per_workspace"),
+        );
+
+        assert_eq!(cache.normal_map["notes"], "notes-{workspace}");
+        assert_eq!(cache.normal_map["btop"], "btop");
+        assert_eq!(cache.clean_map["files"], "files-{workspace}");
+        assert_eq!(expand_workspace(&cache.clean_map["files"], 3), "files-3");
+    }
+
     struct ReloadResources {
         config_contents_a: &'static [u8],
         config_contents_b: &'static [u8],
@@ -922,7 +1694,10 @@ bind = $mainMod, d, exec, hyprscratch cmat 'kitty --title cmat -e cmat' special\
                 config_file: config_file.to_string(),
                 daemon_options: String::new(),
                 groups: HashMap::new(),
+                profiles: HashMap::new(),
                 names: vec!["firefox".into(), "btop".into(), "htop".into(), "cmat".into()],
+                instances: HashMap::new(),
+                attached: HashMap::new(),
                 scratchpads: create_scratchpads(vec![
                 Scratchpad::new("firefox", "firefox", "", "cover"),
                 Scratchpad::new(
@@ -962,7 +1737,10 @@ bind = $mainMod, d, exec, hyprscratch cmat 'kitty --title cmat -e cmat' special\
                 config_file: config_file.to_string(),
                 daemon_options: String::new(),
                 groups: HashMap::new(),
+                profiles: HashMap::new(),
                 names: vec!["firefox".into(), "btop".into(), "htop".into(), "cmat".into()],
+                instances: HashMap::new(),
+                attached: HashMap::new(),
                 scratchpads: create_scratchpads(vec![
                 Scratchpad::new(
                     "firefox",
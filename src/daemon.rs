@@ -1,66 +1,347 @@
-use crate::config::Config;
+use crate::binds::{init_binds, register_binds};
+use crate::config::{Config, Profile};
 use crate::dispatchers::dispatchers;
 use crate::event::start_event_listeners;
+use crate::hotplug::snapshot_monitors;
+use crate::layout::{arrange, Layout};
 use crate::logs::*;
-use crate::scratchpad::Scratchpad;
+use crate::scratchpad::{get_monitors, resolve_monitor, Hook, Location, Scratchpad};
+use crate::supervisor::{run_client_hook, run_config_hook, supervisor};
 use crate::utils::*;
 use crate::DEFAULT_SOCKET;
 use crate::HYPRSCRATCH_DIR;
-use hyprland::data::{Client, Clients};
+use hyprland::data::{Client, Clients, Monitors, Workspace, Workspaces};
 use hyprland::dispatch::WindowIdentifier;
 use hyprland::error::HyprError;
 use hyprland::keyword::Keyword;
 use hyprland::prelude::*;
+use hyprland::shared::{Address, MonitorId, WorkspaceId};
 use hyprland::Result;
-use std::fs::{create_dir, remove_file};
+use std::collections::HashMap;
+use std::fs::{create_dir, read_to_string, remove_file, write};
 use std::io::Write;
 use std::os::unix::net::{UnixListener, UnixStream};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 
 type ConfigMutex = Arc<RwLock<Config>>;
 
-#[derive(Clone, Copy)]
 pub struct DaemonOptions {
     pub eager: bool,
-    pub clean: bool,
-    pub spotless: bool,
+    clean: AtomicBool,
+    spotless: AtomicBool,
     pub auto_reload: bool,
+    pub layout: Option<Layout>,
 }
 
 impl DaemonOptions {
     pub fn new(opts: &str, config: &Config) -> DaemonOptions {
-        let options = format!("{opts} {}", config.daemon_options);
+        Self::parse(&format!("{opts} {}", config.daemon_options))
+    }
+
+    fn parse(options: &str) -> DaemonOptions {
+        let args: Vec<String> = options.split_whitespace().map(str::to_string).collect();
         DaemonOptions {
             eager: options.contains("eager"),
-            clean: options.contains("clean"),
-            spotless: options.contains("spotless"),
+            clean: AtomicBool::new(options.contains("clean")),
+            spotless: AtomicBool::new(options.contains("spotless")),
             auto_reload: !options.contains("no-auto-reload"),
+            layout: get_flag_arg(&args, "layout").and_then(|l| Layout::new(&l)),
+        }
+    }
+
+    pub fn clean(&self) -> bool {
+        self.clean.load(Ordering::Relaxed)
+    }
+
+    pub fn spotless(&self) -> bool {
+        self.spotless.load(Ordering::Relaxed)
+    }
+
+    fn switch(&self, other: &DaemonOptions) {
+        self.clean.store(other.clean(), Ordering::Relaxed);
+        self.spotless.store(other.spotless(), Ordering::Relaxed);
+    }
+}
+
+struct CycleOptions {
+    reverse: bool,
+    mru: bool,
+    monitor: bool,
+    special: Option<bool>,
+    group: Option<String>,
+}
+
+impl CycleOptions {
+    fn new(msg: &str) -> CycleOptions {
+        let args: Vec<&str> = msg
+            .split_whitespace()
+            .map(|a| a.trim_start_matches('-'))
+            .collect();
+
+        let special = if args.contains(&"special") {
+            Some(true)
+        } else if args.contains(&"normal") {
+            Some(false)
+        } else {
+            None
+        };
+
+        CycleOptions {
+            reverse: args.contains(&"reverse"),
+            mru: args.contains(&"mru"),
+            monitor: args.contains(&"monitor"),
+            special,
+            group: args
+                .iter()
+                .find_map(|a| a.strip_prefix("group:"))
+                .map(str::to_string),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HiddenWindow {
+    address: Address,
+    workspace: WorkspaceId,
+    monitor: Option<MonitorId>,
+    at: (i16, i16),
+    pinned: bool,
+}
+
+impl HiddenWindow {
+    fn new(cl: &Client) -> HiddenWindow {
+        HiddenWindow {
+            address: cl.address.clone(),
+            workspace: cl.workspace.id,
+            monitor: cl.monitor,
+            at: cl.at,
+            pinned: cl.pinned,
+        }
+    }
+
+    fn target(
+        &self,
+        existing: &[WorkspaceId],
+        monitors: &[(MonitorId, WorkspaceId)],
+    ) -> WorkspaceId {
+        if existing.contains(&self.workspace) {
+            return self.workspace;
+        }
+
+        monitors
+            .iter()
+            .find(|(id, _)| Some(*id) == self.monitor)
+            .map_or(self.workspace, |(_, active)| *active)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HiddenAll {
+    windows: Vec<HiddenWindow>,
+    special: Option<String>,
+}
+
+impl HiddenAll {
+    fn merge(&mut self, other: HiddenAll) {
+        for window in other.windows {
+            if !self.windows.iter().any(|w| w.address == window.address) {
+                self.windows.push(window);
+            }
         }
+        self.special = self.special.take().or(other.special);
     }
 }
 
 #[derive(Clone)]
 pub struct DaemonState {
-    pub cycle_index: usize,
-    pub prev_titles: [String; 2],
+    pub last_cycled: Option<String>,
+    pub mru_order: Vec<String>,
+    pub history: Vec<String>,
+    pub group_active: HashMap<String, String>,
+    pub hidden_all: Option<HiddenAll>,
+    pub profile: Option<String>,
     pub options: Arc<DaemonOptions>,
+    args: String,
 }
 
 impl DaemonState {
     pub fn new(args: &str, config: &Config) -> DaemonState {
         DaemonState {
-            cycle_index: 0,
-            prev_titles: [String::new(), String::new()],
+            last_cycled: None,
+            mru_order: Vec::new(),
+            history: Vec::new(),
+            group_active: HashMap::new(),
+            hidden_all: None,
+            profile: None,
             options: Arc::new(DaemonOptions::new(args, config)),
+            args: args.into(),
         }
     }
 
-    fn update_prev_titles(&mut self, new_title: &str) {
-        if new_title != self.prev_titles[0] {
-            self.prev_titles[1] = self.prev_titles[0].clone();
-            self.prev_titles[0] = new_title.to_string();
+    fn profile_members(&self, config: &Config) -> Option<Vec<String>> {
+        let profile = config.profiles.get(self.profile.as_ref()?)?;
+        Some(config.get_profile_members(profile))
+    }
+
+    fn in_profile(&self, name: &str, config: &Config) -> bool {
+        let name = config.instances.get(name).map_or(name, |i| &i.template);
+        self.profile_members(config)
+            .is_none_or(|members| members.iter().any(|n| n == name))
+    }
+
+    fn apply_profile(&self, profile: &Profile) {
+        let options = DaemonOptions::parse(&format!("{} {}", self.args, profile.daemon_options));
+        self.options.switch(&options);
+    }
+
+    fn record_hidden(&mut self, hidden: HiddenAll) {
+        match &mut self.hidden_all {
+            Some(hidden_all) => hidden_all.merge(hidden),
+            None => self.hidden_all = Some(hidden),
+        }
+    }
+
+    fn update_history(&mut self, name: &str) {
+        self.history.retain(|n| n != name);
+        self.history.insert(0, name.to_string());
+    }
+
+    fn prune_history(&mut self, config: &Config) {
+        self.history.retain(|n| match n.split_once(':') {
+            Some(("group", group)) => config.groups.contains_key(group),
+            _ => config.get(n).is_some(),
+        });
+    }
+
+    fn serialize(&self, config: &Config) -> String {
+        let mut lines = vec![];
+        if let Some(name) = &self.last_cycled {
+            lines.push(format!("last_cycled\t{name}"));
+        }
+        if let Some(profile) = &self.profile {
+            lines.push(format!("profile\t{profile}"));
         }
+        lines.extend(self.history.iter().map(|n| format!("history\t{n}")));
+        lines.extend(self.mru_order.iter().map(|n| format!("mru\t{n}")));
+
+        let mut groups: Vec<_> = self.group_active.iter().collect();
+        groups.sort();
+        lines.extend(
+            groups
+                .iter()
+                .map(|(g, n)| format!("group_active\t{g}\t{n}")),
+        );
+
+        let mut attached: Vec<_> = config.attached.iter().collect();
+        attached.sort_by_key(|(name, _)| *name);
+        lines.extend(attached.iter().map(|(name, sc)| {
+            let options = sc.options.as_str();
+            format!("attached\t{name}\t{}\t{}\t{options}", sc.title, sc.command)
+        }));
+
+        let mut instances: Vec<_> = config.instances.iter().collect();
+        instances.sort_by_key(|(key, _)| *key);
+        lines.extend(instances.iter().map(|(_, instance)| {
+            let mut args: Vec<_> = instance.args.iter().collect();
+            args.sort();
+            let args = args.iter().map(|(k, v)| format!("\t{k}={v}"));
+            format!(
+                "instance\t{}{}",
+                instance.template,
+                args.collect::<String>()
+            )
+        }));
+        lines.join("\n")
+    }
+
+    fn restore(&mut self, saved: &str, config: &mut Config, live: &[(String, String)]) {
+        for line in saved.lines() {
+            match line.split('\t').collect::<Vec<_>>()[..] {
+                ["last_cycled", name] => self.last_cycled = Some(name.into()),
+                ["profile", name] => self.profile = Some(name.into()),
+                ["history", name] => self.history.push(name.into()),
+                ["mru", name] => self.mru_order.push(name.into()),
+                ["group_active", group, name] => {
+                    self.group_active.insert(group.into(), name.into());
+                }
+                ["attached", name, title, command, options] => {
+                    let sc = Scratchpad::new(title, command, "", options);
+                    if live.iter().any(|(t, c)| sc.matches(t, c)) {
+                        config.attach(name, &sc);
+                    }
+                }
+                ["instance", template, ref args @ ..] => {
+                    let args = args
+                        .iter()
+                        .filter_map(|arg| arg.split_once('='))
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect();
+                    let key = match config.add_instance(template, &args) {
+                        Ok(key) => key,
+                        Err(_) => continue,
+                    };
+                    let sc = &config.instances[&key].scratchpad;
+                    if !live.iter().any(|(t, c)| sc.matches(t, c)) {
+                        config.remove_instance(&key);
+                    }
+                }
+                _ => {
+                    let _ = log(format!("Invalid state entry '{line}'"), Warn);
+                }
+            }
+        }
+
+        self.profile = self
+            .profile
+            .take()
+            .filter(|p| config.profiles.contains_key(p));
+        if let Some(profile) = self.profile.as_ref().map(|p| &config.profiles[p]) {
+            self.apply_profile(profile);
+        }
+
+        let exists = |n: &String| config.get(n).is_some();
+        self.last_cycled = self.last_cycled.take().filter(exists);
+        self.mru_order.retain(exists);
+        self.group_active.retain(|group, name| {
+            config
+                .groups
+                .get(group)
+                .is_some_and(|g| g.names.contains(name))
+        });
+        self.prune_history(config);
+    }
+}
+
+fn get_state_file(socket_path: Option<&str>) -> PathBuf {
+    get_sock(socket_path).with_extension("state")
+}
+
+fn load_state(state: &mut DaemonState, config: &mut Config, socket_path: Option<&str>) {
+    let saved = match read_to_string(get_state_file(socket_path)) {
+        Ok(saved) => saved,
+        Err(_) => return,
+    };
+
+    let live: Vec<(String, String)> = Clients::get()
+        .map(|clients| {
+            clients
+                .into_iter()
+                .map(|cl| (cl.initial_title, cl.initial_class))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    state.restore(&saved, config, &live);
+    let _ = log("Restored daemon state".into(), Info);
+}
+
+fn save_state(state: &DaemonState, config: &Config, path: &Path, last_saved: &mut String) {
+    let current = state.serialize(config);
+    if current != *last_saved {
+        write(path, &current).log_err(file!(), line!());
+        *last_saved = current;
     }
 }
 
@@ -81,54 +362,108 @@ impl<'a> RequestData<'a> {
         }
     }
 
-    fn get_new_index(&mut self) {
-        let warn_empty = |titles: &[_]| {
-            if titles.is_empty() {
-                let _ = log(format!("No {} scratchpads found", self.msg), Warn);
-                return true;
-            }
-            false
+    fn get_cycle_order(&mut self, opts: &CycleOptions) -> Vec<String> {
+        if !opts.mru {
+            self.state.mru_order.clear();
+            return self.config.names.clone();
+        }
+
+        let continuing = !self.state.mru_order.is_empty()
+            && self.state.history.first() == self.state.last_cycled.as_ref();
+
+        if !continuing {
+            self.state.prune_history(self.config);
+            let mut order: Vec<String> = self
+                .state
+                .history
+                .iter()
+                .filter(|n| self.config.scratchpads.contains_key(*n))
+                .cloned()
+                .collect();
+
+            let rest: Vec<String> = self
+                .config
+                .names
+                .iter()
+                .filter(|n| !order.contains(n))
+                .cloned()
+                .collect();
+
+            order.extend(rest);
+            self.state.mru_order = order;
+            self.state.last_cycled = None;
+        }
+        self.state.mru_order.clone()
+    }
+
+    fn is_cycle_candidate(
+        &self,
+        name: &str,
+        opts: &CycleOptions,
+        monitors: &Option<HashMap<String, String>>,
+    ) -> bool {
+        let sc = match self.config.get(name) {
+            Some(sc) => sc,
+            None => return false,
         };
 
-        let len = self.config.scratchpads.len();
-        let find_next = |mode| -> usize {
-            let mut index = (self.state.cycle_index + 1) % len;
-            while mode
-                == self.config.scratchpads[&self.config.names[index]]
-                    .options
-                    .special
-            {
-                index = (index + 1) % len;
-            }
-            index
+        let in_group = |group: &String| {
+            self.config
+                .groups
+                .get(group)
+                .is_some_and(|g| g.names.iter().any(|n| n == name))
         };
 
-        let index = if self.msg.contains("special") {
-            if warn_empty(&self.config.cache.special_titles) {
-                return;
-            }
-            find_next(false)
-        } else if self.msg.contains("normal") {
-            if warn_empty(&self.config.cache.normal_titles) {
-                return;
-            }
-            find_next(true)
-        } else {
-            if warn_empty(&self.config.names) {
-                return;
-            }
-            (self.state.cycle_index + 1) % len
+        let on_monitor = |monitors: &HashMap<String, String>| match &sc.options.monitor {
+            Some(m) => resolve_monitor(monitors, m).as_ref() == monitors.get("focused"),
+            None => true,
         };
 
-        self.state.cycle_index = index;
+        opts.special != Some(!sc.options.special)
+            && !sc.is_parameterized()
+            && self.state.in_profile(name, self.config)
+            && opts.group.as_ref().is_none_or(in_group)
+            && monitors.as_ref().is_none_or(on_monitor)
     }
 
     fn get_next_name(&mut self) -> Option<String> {
-        self.get_new_index();
-        self.state.update_prev_titles(
-            &self.config.scratchpads[&self.config.names[self.state.cycle_index]].title,
-        );
-        Some((&self.config.names[self.state.cycle_index]).into())
+        let opts = CycleOptions::new(&self.msg);
+        let order = self.get_cycle_order(&opts);
+        let monitors = if opts.monitor {
+            get_monitors(None).ok()
+        } else {
+            None
+        };
+
+        let len = order.len();
+        let start = self
+            .state
+            .last_cycled
+            .as_ref()
+            .and_then(|l| order.iter().position(|n| n == l))
+            .unwrap_or(0);
+
+        let step = |k: usize| {
+            if opts.reverse {
+                (start + len - k) % len
+            } else {
+                (start + k) % len
+            }
+        };
+
+        let next = (1..=len)
+            .map(|k| &order[step(k)])
+            .find(|n| self.is_cycle_candidate(n, &opts, &monitors))
+            .cloned();
+
+        match &next {
+            Some(name) => self.state.last_cycled = Some(name.clone()),
+            None => {
+                let msg = format!("No scratchpads found for 'cycle {}'", self.msg.trim());
+                let _ = log(msg, Warn);
+            }
+        }
+        next
     }
 
     fn get_config_path(&self) -> Option<String> {
@@ -142,12 +477,54 @@ impl<'a> RequestData<'a> {
 
 fn trigger_action(sc: &mut Scratchpad, data: &mut RequestData) -> Result<()> {
     sc.options.toggle(&data.req);
-    sc.trigger(&data.config.cache.replace_map, &data.msg)?;
+    sc.trigger(data.config, &data.msg)?;
     Ok(())
 }
 
+fn hide_siblings(data: &mut RequestData) -> Result<()> {
+    let exclusive = data
+        .config
+        .groups
+        .iter()
+        .filter(|(_, g)| g.exclusive && g.names.contains(&data.msg));
+
+    for (group, members) in exclusive {
+        for name in members.names.iter().filter(|n| **n != data.msg) {
+            hide_scratchpad(data.config, name)?;
+        }
+        data.state
+            .group_active
+            .insert(group.clone(), data.msg.clone());
+    }
+    Ok(())
+}
+
+fn needs_arguments(name: &str, sc: &Scratchpad) -> bool {
+    if sc.is_parameterized() {
+        let msg = format!("Scratchpad '{name}' takes arguments and cannot be shown without them");
+        let _ = log(msg, Warn);
+    }
+    sc.is_parameterized()
+}
+
+fn is_permitted(data: &RequestData, sc: &Scratchpad) -> Result<bool> {
+    if !data.state.in_profile(&data.msg, data.config) {
+        let profile = data.state.profile.as_deref().unwrap_or_default();
+        let msg = format!("Scratchpad '{}' is not in profile '{profile}'", data.msg);
+        log(msg, Info)?;
+        return Ok(false);
+    }
+
+    if sc.is_restricted() && !sc.is_allowed(&Location::get()?) {
+        let msg = format!("Scratchpad '{}' is not allowed here", data.msg);
+        log(msg, Info)?;
+        return Ok(false);
+    }
+    Ok(true)
+}
+
 fn handle_scratchpad(data: &mut RequestData) -> Result<()> {
-    let mut sc = match data.config.scratchpads.get_mut(data.msg.as_str()) {
+    let mut sc = match data.config.get(&data.msg) {
         Some(sc) => sc.clone(),
         None => {
             let _ = log(format!("Scratchpad '{}' not found", data.msg), Warn);
@@ -155,7 +532,16 @@ fn handle_scratchpad(data: &mut RequestData) -> Result<()> {
         }
     };
 
-    data.state.update_prev_titles(&sc.title);
+    if needs_arguments(&data.msg, &sc) || !is_permitted(data, &sc)? {
+        return Ok(());
+    }
+
+    if data.req != "hide" {
+        hide_siblings(data)?;
+    }
+
+    data.state.update_history(&data.msg);
+    supervisor().reset(&data.msg);
     trigger_action(&mut sc, data)
 }
 
@@ -168,14 +554,33 @@ fn handle_group(data: &mut RequestData) -> Result<()> {
         }
     };
 
-    if group.is_empty() {
+    let members: Vec<(String, Scratchpad)> = group
+        .names
+        .into_iter()
+        .zip(group.scratchpads)
+        .filter(|(name, sc)| !needs_arguments(name, sc))
+        .collect();
+
+    if members.is_empty() {
         return Ok(());
     }
 
-    data.state.update_prev_titles(&group.last().unwrap().title);
+    data.state.update_history(&format!("group:{}", data.msg));
+
+    if group.exclusive {
+        data.msg = data
+            .state
+            .group_active
+            .get(&data.msg)
+            .filter(|n| members.iter().any(|(name, _)| name == *n))
+            .unwrap_or(&members[0].0)
+            .clone();
+        return handle_scratchpad(data);
+    }
 
-    for mut sc in group {
+    for (name, mut sc) in members {
         sc.options.cover = true;
+        data.msg = name;
         trigger_action(&mut sc, data)?;
     }
     Ok(())
@@ -186,35 +591,132 @@ fn handle_cycle(mut data: RequestData) -> Result<()> {
         return log("No scratchpads configured for 'cycle'".into(), Warn);
     }
 
+    let prev = data
+        .state
+        .last_cycled
+        .clone()
+        .filter(|p| data.state.history.first() == Some(p));
     if let Some(name) = data.get_next_name() {
+        let persist = |p: &String| data.config.get(p).is_some_and(|sc| sc.options.persist);
+        if let Some(prev) = prev.filter(|p| *p != name && !persist(p)) {
+            hide_scratchpad(data.config, &prev)?;
+        }
+
         data.msg = name;
         handle_scratchpad(&mut data)?;
-        return Ok(());
     }
 
     Ok(())
 }
 
+fn hide_scratchpad(config: &Config, name: &str) -> Result<()> {
+    let mut sc = match config.get(name) {
+        Some(sc) if !sc.is_parameterized() => sc.clone(),
+        _ => return Ok(()),
+    };
+
+    sc.options.hide = true;
+    sc.trigger(config, name)
+}
+
+fn get_previous(data: &RequestData, steps: usize) -> Option<String> {
+    let active = Client::get_active().ok().flatten();
+    let is_active = |name: &&String| match (data.config.get(name), &active) {
+        (Some(sc), Some(ac)) => sc.matches_client(ac),
+        _ => false,
+    };
+
+    let mut history = data.state.history.iter().peekable();
+    history.next_if(is_active);
+    history.nth(steps - 1).cloned()
+}
+
 fn handle_previous(mut data: RequestData) -> Result<()> {
-    if data.state.prev_titles[0].is_empty() {
+    data.state.prune_history(data.config);
+    if data.state.history.is_empty() {
         return log("No previous scratchpads exist".into(), Warn);
     }
 
-    let is_prev = |ac: &Client| {
-        ac.initial_class == data.state.prev_titles[0]
-            || ac.initial_title == data.state.prev_titles[0]
+    let args: Vec<String> = data.msg.split_whitespace().map(str::to_string).collect();
+    let steps = args
+        .iter()
+        .find_map(|a| a.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1);
+
+    data.msg = match get_previous(&data, steps) {
+        Some(name) => name,
+        None => {
+            let msg = format!("Only {} scratchpads in history", data.state.history.len());
+            return log(msg, Warn);
+        }
     };
 
-    data.msg = match Client::get_active() {
-        Ok(Some(ac)) if is_prev(&ac) => data.state.prev_titles[1].clone(),
-        _ => data.state.prev_titles[0].clone(),
+    if let Some(mode) = args.into_iter().find(|a| a == "show" || a == "hide") {
+        data.req = mode;
+    }
+    handle_call(data)
+}
+
+fn handle_history(stream: &mut UnixStream, data: RequestData) -> Result<()> {
+    data.state.prune_history(data.config);
+    let history = data
+        .state
+        .history
+        .iter()
+        .enumerate()
+        .fold(String::new(), |acc, (i, name)| {
+            acc + &format!("{}: {name}\n", i + 1)
+        });
+    stream.write_all(history.as_bytes())?;
+    Ok(())
+}
+
+fn parse_call_args(msg: &str) -> (String, HashMap<String, String>) {
+    let mut parts = msg.split('^');
+    let name = parts.next().unwrap_or_default().to_string();
+    let args = parts
+        .filter_map(|arg| arg.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    (name, args)
+}
+
+fn resolve_instance(data: &mut RequestData, args: &HashMap<String, String>) -> bool {
+    match data.config.scratchpads.get(&data.msg) {
+        Some(sc) if sc.is_parameterized() => (),
+        _ => return true,
+    };
+
+    match data.config.add_instance(&data.msg, args) {
+        Ok(key) => {
+            data.msg = key;
+            true
+        }
+        Err(arg) => {
+            let msg = format!("Missing argument '{arg}' for scratchpad '{}'", data.msg);
+            let _ = log(msg, Warn);
+            false
+        }
+    }
+}
+
+fn redirect(data: &mut RequestData) -> Result<()> {
+    let target = match data.config.scratchpads.get(&data.msg) {
+        Some(sc) if !sc.when.is_empty() => sc.redirect(&Location::get()?).map(str::to_string),
+        _ => None,
     };
 
-    handle_scratchpad(&mut data)?;
+    if let Some(target) = target {
+        data.msg = target;
+    }
     Ok(())
 }
 
 fn handle_call(mut data: RequestData) -> Result<()> {
+    let (name, args) = parse_call_args(&data.msg);
+    data.msg = name;
+
     if data.msg.is_empty() {
         return log(
             format!("No scratchpad or group title given to '{}'", data.req),
@@ -222,6 +724,7 @@ fn handle_call(mut data: RequestData) -> Result<()> {
         );
     }
 
+    redirect(&mut data)?;
     if let Some(("group", name)) = data.msg.split_once(":") {
         if data.config.groups.contains_key(name) {
             data.msg = name.to_string();
@@ -229,22 +732,167 @@ fn handle_call(mut data: RequestData) -> Result<()> {
         }
     }
 
+    if !resolve_instance(&mut data, &args) {
+        return Ok(());
+    }
     handle_scratchpad(&mut data)
 }
 
+fn handle_swap(mut data: RequestData) -> Result<()> {
+    let active = match Client::get_active()? {
+        Some(active) => active,
+        None => return Ok(()),
+    };
+
+    redirect(&mut data)?;
+    if data.msg.starts_with("group:") {
+        return log(format!("Cannot swap with '{}'", data.msg), Warn);
+    }
+
+    let name = data
+        .config
+        .get_name(&active.initial_title, &active.initial_class);
+    let old = match name {
+        Some(old) if old != data.msg => old,
+        Some(_) => return Ok(()),
+        None => return log("Focused window is not a scratchpad".into(), Warn),
+    };
+
+    let mut sc = match data.config.get(&data.msg) {
+        Some(sc) => sc.clone(),
+        None => return log(format!("Scratchpad '{}' not found", data.msg), Warn),
+    };
+
+    if needs_arguments(&data.msg, &sc) || !is_permitted(&data, &sc)? {
+        return Ok(());
+    }
+
+    let old_sc = match data.config.get(&old) {
+        Some(old_sc) => old_sc.clone(),
+        None => return Ok(()),
+    };
+    if sc.options.special || old_sc.options.special {
+        return log("Special scratchpads cannot be swapped".into(), Warn);
+    }
+
+    let ((x, y), (w, h)) = (active.at, active.size);
+    match Clients::get()?.into_iter().find(|cl| sc.matches_client(cl)) {
+        Some(cl) => {
+            restore_window(&cl, active.workspace.id, active.at, sc.options.pin);
+            run_client_hook(Hook::Show, &sc.hooks, &data.msg, &cl);
+            let win = || WindowIdentifier::Address(cl.address.clone());
+            dispatchers().resize_window_pixel((w.into(), h.into()), win())?;
+            dispatchers().focus_window(win())?;
+        }
+        None => {
+            sc.rules = format!("{}; move {x} {y}; size {w} {h}", sc.rules);
+            sc.options.show = true;
+            sc.options.cover = true;
+            sc.trigger(data.config, &data.msg)?;
+        }
+    }
+
+    let workspace = old_sc.hide_workspace(&old);
+    move_to_special(&active, &expand_workspace(&workspace, active.workspace.id));
+    run_client_hook(Hook::Hide, &old_sc.hooks, &old, &active);
+    data.state.update_history(&data.msg);
+    Ok(())
+}
+
+fn handle_profile(data: RequestData) -> Result<()> {
+    let profile = match data.config.profiles.get(&data.msg) {
+        Some(profile) => profile.clone(),
+        None => return log(format!("Profile '{}' not found", data.msg), Warn),
+    };
+
+    let members = data.config.get_profile_members(&profile);
+    let clients = Clients::get()?;
+    let active = Client::get_active()?;
+
+    for (name, sc) in data.config.scratchpads.iter() {
+        if members.contains(name) {
+            continue;
+        }
+
+        for cl in clients.iter().filter(|cl| sc.matches_client(cl)) {
+            if profile.close {
+                supervisor().expect_close(&cl.address);
+                dispatchers()
+                    .close_window(WindowIdentifier::Address(cl.address.clone()))
+                    .log_err(file!(), line!());
+            } else if !is_on_special(cl) {
+                let workspace = expand_workspace(&sc.hide_workspace(name), cl.workspace.id);
+                move_to_special(cl, &workspace);
+                run_client_hook(Hook::Hide, &sc.hooks, name, cl);
+            } else if active.as_ref().is_some_and(|ac| ac.address == cl.address) {
+                hide_special(cl);
+            }
+        }
+    }
+
+    data.state.apply_profile(&profile);
+    data.state.profile = Some(data.msg.clone());
+    log(format!("Switched to profile '{}'", data.msg), Info)
+}
+
 fn handle_attach(data: RequestData) -> Result<()> {
-    if let Some(client) = Client::get_active()? {
-        let class = client.initial_class;
-        let scratchpad = Scratchpad::new(&class, "", "", &data.msg);
-        data.config.add_scratchpad(&class, &scratchpad);
+    let client = match Client::get_active()? {
+        Some(client) => client,
+        None => return Ok(()),
+    };
+
+    let (name, options, save) = match data.msg.splitn(3, '^').collect::<Vec<_>>()[..] {
+        [name, options, save] => (name, options, save == "true"),
+        _ => ("", data.msg.as_str(), false),
+    };
+
+    let class = client.initial_class;
+    let name = if name.is_empty() { &class } else { name };
+    if data.config.scratchpads.contains_key(name) {
+        return log(format!("Scratchpad '{name}' already exists"), Warn);
+    }
+
+    let mut scratchpad = Scratchpad::new(&class, "", "", options);
+    if save {
+        scratchpad.command = get_cmdline(client.pid).unwrap_or_default();
+        data.config.save_scratchpad(name, &scratchpad)?;
     }
 
+    data.config.attach(name, &scratchpad);
     Ok(())
 }
 
+fn handle_detach(data: RequestData) -> Result<()> {
+    let name = if data.msg.is_empty() {
+        Client::get_active()?
+            .and_then(|cl| data.config.get_name(&cl.initial_title, &cl.initial_class))
+    } else {
+        Some(data.msg.clone())
+    };
+
+    let removed = name.and_then(|n| data.config.remove_scratchpad(&n).map(|sc| (n, sc)));
+    let (name, sc) = match removed {
+        Some(removed) => removed,
+        None if data.msg.is_empty() => {
+            return log("Focused window is not a scratchpad".into(), Warn);
+        }
+        None => return log(format!("Scratchpad '{}' not found", data.msg), Warn),
+    };
+
+    data.state.history.retain(|n| *n != name);
+
+    let workspace = Workspace::get_active()?.id;
+    Clients::get()?
+        .iter()
+        .filter(|cl| sc.matches_client(cl))
+        .for_each(|cl| release_window(cl, workspace));
+
+    log(format!("Scratchpad '{name}' detached"), Info)
+}
+
 fn handle_manual(mut data: RequestData) -> Result<()> {
     let args: Vec<&str> = data.msg.splitn(3, '^').collect();
-    data.state.update_prev_titles(args[0]);
+    data.state.update_history(args[0]);
 
     let mut scratchpad = Scratchpad::new(args[0], args[1], "", &args[2..].join(" "));
     data.config.add_scratchpad(args[0], &scratchpad);
@@ -257,8 +905,17 @@ fn handle_manual(mut data: RequestData) -> Result<()> {
 
 fn handle_reload(data: RequestData) -> Result<()> {
     data.config.reload(data.get_config_path())?;
+    data.state.prune_history(data.config);
+    if let Some(profile) = &data.state.profile {
+        if !data.config.profiles.contains_key(profile) {
+            data.state.profile = None;
+        }
+    }
+    supervisor().sync(data.config)?;
+    register_binds(data.config)?;
     if data.state.options.eager {
-        autospawn(data.config)?;
+        let members = data.state.profile_members(data.config);
+        autospawn(data.config, members.as_deref())?;
     }
 
     log("Configuration reloaded".to_string(), Info)?;
@@ -273,12 +930,12 @@ fn handle_get_config(stream: &mut UnixStream, data: RequestData) -> Result<()> {
 fn handle_killall(data: RequestData) -> Result<()> {
     let is_scratchpad = |cl: &Client| {
         data.config
-            .scratchpads
-            .values()
-            .any(|sc| sc.matches_client(cl))
+            .all_scratchpads()
+            .any(|(_, sc)| sc.matches_client(cl))
     };
 
     let kill = |cl: Client| {
+        supervisor().expect_close(&cl.address);
         dispatchers()
             .close_window(WindowIdentifier::Address(cl.address))
             .log_err(file!(), line!());
@@ -292,10 +949,78 @@ fn handle_killall(data: RequestData) -> Result<()> {
 }
 
 fn handle_hideall(data: RequestData) -> Result<()> {
-    move_floating(&data.config.cache.normal_map)?;
+    if data.msg.contains("toggle") && data.state.hidden_all.is_some() {
+        return handle_restoreall(data);
+    }
+
+    let mut hidden = HiddenAll::default();
+    let clients = hide_floating(data.config, &data.config.cache.normal_map)?;
+    hidden.windows = clients.iter().map(HiddenWindow::new).collect();
+
     if let Ok(Some(ac)) = Client::get_active() {
+        if let Some(("special", workspace)) = ac.workspace.name.split_once(':') {
+            hidden.special = Some(workspace.into());
+        }
         hide_special(&ac);
     }
+
+    if hidden != HiddenAll::default() {
+        data.state.record_hidden(hidden);
+    }
+    Ok(())
+}
+
+fn handle_restoreall(data: RequestData) -> Result<()> {
+    let hidden = match data.state.hidden_all.take() {
+        Some(hidden) => hidden,
+        None => {
+            return log(
+                "No scratchpads hidden by 'hide-all' to restore".into(),
+                Info,
+            )
+        }
+    };
+
+    let clients = Clients::get()?;
+    let existing: Vec<_> = Workspaces::get()?.into_iter().map(|ws| ws.id).collect();
+    let monitors: Vec<_> = Monitors::get()?
+        .into_iter()
+        .map(|m| (m.id, m.active_workspace.id))
+        .collect();
+
+    for window in &hidden.windows {
+        let client = clients
+            .iter()
+            .find(|cl| cl.address == window.address && is_on_special(cl));
+
+        if let Some(cl) = client {
+            let workspace = window.target(&existing, &monitors);
+            restore_window(cl, workspace, window.at, window.pinned);
+            run_config_hook(Hook::Show, data.config, cl);
+        }
+    }
+
+    let mut monitors: Vec<_> = hidden.windows.iter().filter_map(|w| w.monitor).collect();
+    monitors.sort();
+    monitors.dedup();
+    let _ = log(
+        format!(
+            "Restored {} scratchpads on {} monitors",
+            hidden.windows.len(),
+            monitors.len()
+        ),
+        Info,
+    );
+
+    if let Some(special) = hidden.special {
+        dispatchers().toggle_special_workspace(Some(special))?;
+    }
+    Ok(())
+}
+
+fn handle_status(stream: &mut UnixStream, data: RequestData) -> Result<()> {
+    let status = supervisor().status(data.config);
+    stream.write_all(status.as_bytes())?;
     Ok(())
 }
 
@@ -319,11 +1044,17 @@ fn handle_request(data: RequestData, stream: &mut UnixStream) -> Result<()> {
         "previous" => handle_previous(data),
         "kill-all" => handle_killall(data),
         "hide-all" => handle_hideall(data),
+        "restore-all" => handle_restoreall(data),
         "reload" => handle_reload(data),
         "manual" => handle_manual(data),
         "attach" => handle_attach(data),
+        "swap" => handle_swap(data),
+        "profile" => handle_profile(data),
+        "detach" => handle_detach(data),
         "cycle" => handle_cycle(data),
         "menu" => handle_menu(stream, data),
+        "status" => handle_status(stream, data),
+        "history" => handle_history(stream, data),
         "kill" => {
             let _ = log("Recieved 'kill' request, terminating listener".into(), Info);
             Err(HyprError::Other("kill".into()))
@@ -335,6 +1066,26 @@ fn handle_request(data: RequestData, stream: &mut UnixStream) -> Result<()> {
     }
 }
 
+fn arrange_visible(req: &str, state: &DaemonState, config: &Config) {
+    let rearranging = [
+        "toggle",
+        "summon",
+        "show",
+        "hide",
+        "cycle",
+        "previous",
+        "hide-all",
+        "restore-all",
+        "manual",
+    ];
+
+    if let Some(layout) = state.options.layout {
+        if rearranging.contains(&req) {
+            arrange(layout, &config.cache.normal_titles).log_err(file!(), line!());
+        }
+    }
+}
+
 fn get_sock(socket_path: Option<&str>) -> &Path {
     if let Some(sp) = socket_path {
         Path::new(sp)
@@ -365,6 +1116,12 @@ fn start_unix_listener(
     config: ConfigMutex,
 ) -> Result<()> {
     let listener = get_listener(socket_path)?;
+    let state_file = get_state_file(socket_path);
+    let mut last_saved = String::new();
+    let conf = config.read().unwrap_log(file!(), line!());
+    save_state(state, &conf, &state_file, &mut last_saved);
+    drop(conf);
+
     for stream in listener.incoming() {
         match stream {
             Ok(mut stream) => {
@@ -382,10 +1139,11 @@ fn start_unix_listener(
                 let data = RequestData::new(state, conf, req, msg);
 
                 match handle_request(data, &mut stream) {
-                    Ok(()) => (),
+                    Ok(()) => arrange_visible(req, state, conf),
                     Err(HyprError::Other(e)) if e == "kill" => break,
                     Err(e) => log(format!("{e} in '{req} {msg}'"), Warn)?,
                 }
+                save_state(state, conf, &state_file, &mut last_saved);
             }
             Err(_) => {
                 continue;
@@ -397,8 +1155,17 @@ fn start_unix_listener(
 }
 
 fn make_workspaces_persistent(config: &Config) -> Result<()> {
-    for name in config.scratchpads.keys() {
-        let rule = format!("special:{name}, persistent:true");
+    let mut workspaces: Vec<String> = config
+        .scratchpads
+        .iter()
+        .filter(|(_, sc)| !sc.options.per_workspace)
+        .map(|(name, sc)| sc.hide_workspace(name))
+        .collect();
+    workspaces.sort();
+    workspaces.dedup();
+
+    for workspace in workspaces {
+        let rule = format!("special:{workspace}, persistent:true");
         Keyword::set("workspace", rule)?;
     }
     Ok(())
@@ -410,10 +1177,15 @@ pub fn initialize_daemon(args: String, config_path: Option<String>, socket_path:
     let (f, l) = (file!(), line!());
     let mut config = Config::new(config_path).unwrap_log(f, l);
     let mut state = DaemonState::new(&args, &config);
+    load_state(&mut state, &mut config, socket_path);
     make_workspaces_persistent(&config).log_err(f, l);
+    init_binds(&config, socket_path).log_err(f, l);
+    supervisor().sync(&config).log_err(f, l);
+    snapshot_monitors().log_err(f, l);
 
     if state.options.eager {
-        autospawn(&mut config).log_err(f, l);
+        let members = state.profile_members(&config);
+        autospawn(&mut config, members.as_deref()).log_err(f, l);
     }
 
     let config = Arc::new(RwLock::new(config));
@@ -425,6 +1197,7 @@ pub fn initialize_daemon(args: String, config_path: Option<String>, socket_path:
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{Group, Instance};
     use hyprland::data::{Clients, Workspace};
     use hyprland::dispatch::WorkspaceIdentifierWithSpecial;
     use std::io::prelude::*;
@@ -455,6 +1228,8 @@ mod tests {
         test_handle("reload?");
         test_handle("kill-all?");
         test_handle("hide-all?");
+        test_handle("restore-all?");
+        test_handle("swap?unknown");
         test_handle("?unknown");
         test_handle("kill?");
     }
@@ -480,6 +1255,274 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_instances() {
+        let mut config = Config::new(Some("test_configs/test_config3.txt".into())).unwrap();
+        let mut state = DaemonState::new("", &config);
+        let notes = Scratchpad::new(
+            "notes {arg:file}",
+            "kitty --title 'notes {arg:file}' nvim {arg:file}",
+            "",
+            "",
+        );
+        config.add_scratchpad("notes", &notes);
+
+        let (name, args) = parse_call_args("notes^file=~/todo.md");
+        assert_eq!(name, "notes");
+        assert_eq!(args["file"], "~/todo.md");
+
+        let mut data = RequestData::new(&mut state, &mut config, "toggle", "notes");
+        assert!(resolve_instance(&mut data, &args));
+        let key = data.msg.clone();
+        assert_eq!(key, "notes:file=~/todo.md");
+        assert_eq!(config.instances[&key].scratchpad.title, "notes ~/todo.md");
+        assert!(!config.scratchpads.contains_key(&key));
+        assert!(config
+            .cache
+            .normal_titles
+            .contains(&"notes ~/todo.md".into()));
+        assert_eq!(config.get_name("notes ~/todo.md", ""), Some(key.clone()));
+
+        let mut data = RequestData::new(&mut state, &mut config, "toggle", "notes");
+        assert!(!resolve_instance(&mut data, &HashMap::new()));
+        assert_eq!(data.msg, "notes");
+
+        let mut data = RequestData::new(&mut state, &mut config, "toggle", "test_normal");
+        assert!(resolve_instance(&mut data, &args));
+        assert_eq!(data.msg, "test_normal");
+
+        let other = HashMap::from([("file".to_string(), "~/done.md".to_string())]);
+        let mut data = RequestData::new(&mut state, &mut config, "toggle", "notes");
+        assert!(resolve_instance(&mut data, &other));
+        let other_key = data.msg.clone();
+        assert_ne!(other_key, key);
+        assert_eq!(config.instances.len(), 2);
+        assert_eq!(
+            config.instances[&other_key].scratchpad.title,
+            "notes ~/done.md"
+        );
+        assert_eq!(config.get_name("notes ~/done.md", ""), Some(other_key));
+        assert_eq!(
+            config.cache.normal_map["notes ~/todo.md"],
+            "notes_file___todo.md"
+        );
+
+        assert!(config.remove_instance(&key).is_some());
+        assert!(config.remove_instance(&key).is_none());
+        assert_eq!(config.instances.len(), 1);
+        assert_eq!(config.get_name("notes ~/todo.md", ""), None);
+        assert!(!config.cache.normal_map.contains_key("notes ~/todo.md"));
+    }
+
+    #[test]
+    fn test_state_persistence() {
+        let file = env::temp_dir().join(format!(
+            "hyprscratch_state_test_{}.conf",
+            std::process::id()
+        ));
+        let path = file.to_str().unwrap();
+        let blocks = [
+            "test_pin {\n    title = test_pin\n    command = kitty --title test_pin\n}",
+            "test_shiny {\n    class = test_shiny\n    command = kitty --class test_shiny\n}",
+            "notes {\n    title = notes {arg:file}\n    command = kitty --title 'notes {arg:file}'\n}",
+        ];
+        write(path, blocks.join("\n")).unwrap();
+
+        let mut config = Config::new(Some(path.into())).unwrap();
+        let mut state = DaemonState::new("", &config);
+        state.last_cycled = Some("test_pin".into());
+        state.history = vec!["test_pin".into(), "test_removed".into(), "chat".into()];
+        state.mru_order = vec!["test_pin".into(), "test_shiny".into()];
+        state
+            .group_active
+            .insert("missing".into(), "test_pin".into());
+        for name in ["chat", "stale"] {
+            let sc = Scratchpad::new(&format!("{name}_class"), "", "", "persist");
+            config.attach(name, &sc);
+        }
+        let args = HashMap::from([("file".to_string(), "~/todo.md".to_string())]);
+        let key = config.add_instance("notes", &args).unwrap();
+        let closed = HashMap::from([("file".to_string(), "~/done.md".to_string())]);
+        let closed_key = config.add_instance("notes", &closed).unwrap();
+
+        let saved = state.serialize(&config);
+        let live = [
+            ("".to_string(), "chat_class".to_string()),
+            ("notes ~/todo.md".to_string(), "kitty".to_string()),
+        ];
+        let mut config = Config::new(Some(path.into())).unwrap();
+        let mut restored = DaemonState::new("", &config);
+        restored.restore(&saved, &mut config, &live);
+
+        assert_eq!(restored.last_cycled.as_deref(), Some("test_pin"));
+        assert_eq!(restored.history, vec!["test_pin", "chat"]);
+        assert_eq!(restored.mru_order, vec!["test_pin", "test_shiny"]);
+        assert!(restored.group_active.is_empty());
+        assert_eq!(config.attached.keys().collect::<Vec<_>>(), vec!["chat"]);
+        assert!(config.scratchpads["chat"].options.persist);
+        assert!(!config.scratchpads.contains_key("stale"));
+        assert_eq!(config.instances[&key].args, args);
+        assert!(!config.instances.contains_key(&closed_key));
+
+        config.reload(None).unwrap();
+        assert_eq!(config.attached.keys().collect::<Vec<_>>(), vec!["chat"]);
+        assert!(config.scratchpads["chat"].options.persist);
+        assert_eq!(config.get_name("", "chat_class"), Some("chat".into()));
+        assert_eq!(config.get_name("notes ~/todo.md", ""), Some(key.clone()));
+        assert!(config.cache.normal_titles.contains(&"chat_class".into()));
+
+        let saved = restored.serialize(&config);
+        assert!(saved.contains("attached\tchat\tchat_class"));
+        assert!(saved.contains("instance\tnotes\tfile=~/todo.md"));
+        assert!(!saved.contains("file=~/done.md"));
+        remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_hide_all_twice() {
+        let window = |address: &str, workspace, monitor| HiddenWindow {
+            address: Address::new(address),
+            workspace,
+            monitor: Some(monitor),
+            at: (10, 10),
+            pinned: false,
+        };
+
+        let config = Config::new(Some("test_configs/test_config3.txt".into())).unwrap();
+        let mut state = DaemonState::new("", &config);
+        let first = HiddenAll {
+            windows: vec![window("1", 2, 0), window("2", 7, 1)],
+            special: Some("term".into()),
+        };
+        let second = HiddenAll {
+            windows: vec![window("2", 3, 0), window("3", 4, 1)],
+            special: None,
+        };
+
+        state.record_hidden(first);
+        state.record_hidden(second);
+
+        let hidden = state.hidden_all.unwrap();
+        let addresses: Vec<_> = hidden
+            .windows
+            .iter()
+            .map(|w| w.address.to_string())
+            .collect();
+        assert_eq!(addresses, ["0x1", "0x2", "0x3"]);
+        assert_eq!(hidden.windows[1].workspace, 7);
+        assert_eq!(hidden.special.as_deref(), Some("term"));
+
+        let (existing, monitors) = ([2, 5], [(0, 2), (1, 5)]);
+        let targets: Vec<_> = hidden
+            .windows
+            .iter()
+            .map(|w| w.target(&existing, &monitors))
+            .collect();
+        assert_eq!(targets, [2, 5, 5]);
+        assert_eq!(window("4", 9, 3).target(&existing, &monitors), 9);
+    }
+
+    #[test]
+    fn test_profile_switch() {
+        let mut config = Config::new(Some("test_configs/test_config3.txt".into())).unwrap();
+        let profile = Profile {
+            names: vec!["test_pin".into(), "test_shiny".into()],
+            daemon_options: "spotless".into(),
+            close: false,
+        };
+        config.profiles.insert("work".into(), profile.clone());
+
+        let mut state = DaemonState::new("clean", &config);
+        assert!(state.in_profile("test_normal", &config));
+        assert!(state.options.clean() && !state.options.spotless());
+
+        state.profile = Some("work".into());
+        state.apply_profile(&profile);
+        assert!(state.in_profile("test_pin", &config));
+        assert!(!state.in_profile("test_normal", &config));
+        assert_eq!(
+            state.profile_members(&config),
+            Some(vec!["test_pin".into(), "test_shiny".into()])
+        );
+
+        let instance = Instance {
+            template: "test_pin".into(),
+            args: HashMap::new(),
+            scratchpad: config.scratchpads["test_pin"].clone(),
+        };
+        config.instances.insert("test_pin:".into(), instance);
+        assert!(state.in_profile("test_pin:", &config));
+        assert!(state.options.clean() && state.options.spotless());
+
+        let mut data = RequestData::new(&mut state, &mut config, "", "");
+        assert_eq!(data.get_next_name(), Some("test_shiny".into()));
+        assert_eq!(data.get_next_name(), Some("test_pin".into()));
+    }
+
+    #[test]
+    fn test_detach_removes_scratchpad() {
+        let mut config = Config::new(Some("test_configs/test_config3.txt".into())).unwrap();
+        let removed = config.remove_scratchpad("test_sticky").unwrap();
+
+        assert_eq!(removed.title, "test_sticky");
+        let name = "test_sticky".to_string();
+        assert!(!config.names.contains(&name));
+        assert!(!config.cache.normal_titles.contains(&name));
+        assert_eq!(config.remove_scratchpad("test_sticky"), None);
+    }
+
+    #[test]
+    fn test_cycle_modes() {
+        let mut config = Config::new(Some("test_configs/test_config3.txt".into())).unwrap();
+        let mut state = DaemonState::new("", &config);
+        let names = ["test_pin", "test_normal"].map(String::from);
+        let group = Group {
+            scratchpads: names
+                .iter()
+                .map(|n| config.scratchpads[n].clone())
+                .collect(),
+            names: names.to_vec(),
+            ..Default::default()
+        };
+        config.groups.insert("test_group".into(), group);
+
+        let test_cases = vec![
+            ("--reverse", Some("test_pin")),
+            ("--reverse", Some("test_shiny")),
+            ("group:test_group", Some("test_pin")),
+            ("group:test_group", Some("test_normal")),
+            ("--mru", Some("test_pin")),
+            ("--mru", Some("test_shiny")),
+            ("--mru --reverse", Some("test_pin")),
+        ];
+
+        for (message, expected_name) in test_cases {
+            let mut data = RequestData::new(&mut state, &mut config, "cycle", message);
+            let name = data.get_next_name();
+            assert_eq!(name.as_deref(), expected_name);
+            state.update_history(&name.unwrap());
+        }
+
+        config.names.retain(|n| n == "test_special");
+        config.scratchpads.retain(|n, _| n == "test_special");
+        let mut data = RequestData::new(&mut state, &mut config, "cycle", "normal");
+        assert_eq!(data.get_next_name(), None);
+    }
+
+    #[test]
+    fn test_history() {
+        let config = Config::new(Some("test_configs/test_config3.txt".into())).unwrap();
+        let mut state = DaemonState::new("", &config);
+
+        ["test_normal", "test_sticky", "deleted", "test_normal"]
+            .into_iter()
+            .for_each(|name| state.update_history(name));
+        assert_eq!(state.history, ["test_normal", "deleted", "test_sticky"]);
+
+        state.prune_history(&config);
+        assert_eq!(state.history, ["test_normal", "test_sticky"]);
+    }
+
     struct TestResources {
         titles: [String; 4],
         commands: [String; 4],
@@ -508,12 +1551,14 @@ mod tests {
         resources
             .titles
             .clone()
-            .map(|title| assert!(!clients.clone().any(|x| x.initial_title == title)));
+            .into_iter()
+            .for_each(|title| assert!(!clients.clone().any(|x| x.initial_title == title)));
 
         resources
             .commands
             .clone()
-            .map(|command| dispatchers().exec(&command).unwrap());
+            .into_iter()
+            .for_each(|command| dispatchers().exec(&command).unwrap());
         sleep(Duration::from_millis(1000));
     }
 
@@ -716,5 +1761,4 @@ mod tests {
         let mut config_file = File::create(config_path).unwrap();
         config_file.write_all(content.as_bytes()).unwrap();
     }
-
 }
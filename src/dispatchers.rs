@@ -45,7 +45,9 @@ impl Dispatchers {
     pub fn close_window(&self, win: WindowIdentifier<'_>) -> Result<()> {
         match self.lang {
             ConfigLanguage::Hyprlang => call("closewindow", &win.to_string()),
-            ConfigLanguage::Lua => call_lua(&format!("hl.dsp.window.close({})", lua_win_selector(&win))),
+            ConfigLanguage::Lua => {
+                call_lua(&format!("hl.dsp.window.close({})", lua_win_selector(&win)))
+            }
         }
     }
 
@@ -59,7 +61,41 @@ impl Dispatchers {
     pub fn toggle_pin_window(&self, win: WindowIdentifier<'_>) -> Result<()> {
         match self.lang {
             ConfigLanguage::Hyprlang => call("pin", &win.to_string()),
-            ConfigLanguage::Lua => call_lua(&format!("hl.dsp.window.pin({})", lua_win_selector(&win))),
+            ConfigLanguage::Lua => {
+                call_lua(&format!("hl.dsp.window.pin({})", lua_win_selector(&win)))
+            }
+        }
+    }
+
+    pub fn set_floating(&self, win: WindowIdentifier<'_>) -> Result<()> {
+        match self.lang {
+            ConfigLanguage::Hyprlang => call("setfloating", &win.to_string()),
+            ConfigLanguage::Lua => call_lua(&format!(
+                "hl.dsp.window.float({{action=\"set\", window={}}})",
+                lua_str(&win.to_string())
+            )),
+        }
+    }
+
+    pub fn set_prop(&self, prop: &str, value: &str, win: WindowIdentifier<'_>) -> Result<()> {
+        match self.lang {
+            ConfigLanguage::Hyprlang => call("setprop", &format!("{win} {prop} {value}")),
+            ConfigLanguage::Lua => call_lua(&format!(
+                "hl.dsp.window.set_prop({{prop={}, value={}, window={}}})",
+                lua_str(prop),
+                lua_str(value),
+                lua_str(&win.to_string())
+            )),
+        }
+    }
+
+    pub fn set_tiled(&self, win: WindowIdentifier<'_>) -> Result<()> {
+        match self.lang {
+            ConfigLanguage::Hyprlang => call("settiled", &win.to_string()),
+            ConfigLanguage::Lua => call_lua(&format!(
+                "hl.dsp.window.float({{action=\"unset\", window={}}})",
+                lua_str(&win.to_string())
+            )),
         }
     }
 
@@ -96,9 +132,7 @@ impl Dispatchers {
                 None => call("togglespecialworkspace", ""),
             },
             ConfigLanguage::Lua => match name {
-                Some(n) => {
-                    call_lua(&format!("hl.dsp.workspace.toggle_special({})", lua_str(&n)))
-                }
+                Some(n) => call_lua(&format!("hl.dsp.workspace.toggle_special({})", lua_str(&n))),
                 None => call_lua("hl.dsp.workspace.toggle_special()"),
             },
         }
@@ -115,6 +149,45 @@ impl Dispatchers {
         }
     }
 
+    pub fn move_window_pixel(&self, (x, y): (i32, i32), win: WindowIdentifier<'_>) -> Result<()> {
+        match self.lang {
+            ConfigLanguage::Hyprlang => call("movewindowpixel", &format!("exact {x} {y},{win}")),
+            ConfigLanguage::Lua => call_lua(&format!(
+                "hl.dsp.window.move({{x={x}, y={y}, relative=false, window={}}})",
+                lua_str(&win.to_string())
+            )),
+        }
+    }
+
+    pub fn resize_window_pixel(&self, (w, h): (i32, i32), win: WindowIdentifier<'_>) -> Result<()> {
+        match self.lang {
+            ConfigLanguage::Hyprlang => call("resizewindowpixel", &format!("exact {w} {h},{win}")),
+            ConfigLanguage::Lua => call_lua(&format!(
+                "hl.dsp.window.resize({{x={w}, y={h}, relative=false, window={}}})",
+                lua_str(&win.to_string())
+            )),
+        }
+    }
+
+    pub fn set_fullscreen_state(&self, mode: u8) -> Result<()> {
+        match self.lang {
+            ConfigLanguage::Hyprlang => call("fullscreenstate", &format!("{mode} {mode}")),
+            ConfigLanguage::Lua => call_lua(&format!(
+                "hl.dsp.window.fullscreen_state({{internal={mode}, client={mode}}})"
+            )),
+        }
+    }
+
+    pub fn alter_zorder_top(&self, win: WindowIdentifier<'_>) -> Result<()> {
+        match self.lang {
+            ConfigLanguage::Hyprlang => call("alterzorder", &format!("top,{win}")),
+            ConfigLanguage::Lua => call_lua(&format!(
+                "hl.dsp.window.alter_zorder({{mode=\"top\", window={}}})",
+                lua_str(&win.to_string())
+            )),
+        }
+    }
+
     pub fn bring_active_to_top(&self) -> Result<()> {
         match self.lang {
             ConfigLanguage::Hyprlang => call("bringactivetotop", ""),
@@ -1,9 +1,15 @@
-use crate::config::Config;
+use crate::binds::register_binds;
+use crate::config::{is_own_write, Config};
 use crate::daemon::{DaemonOptions, DaemonState};
+use crate::dispatchers::dispatchers;
+use crate::hotplug::{forget_window, rehome_scratchpads, return_scratchpads, snapshot_monitors};
+use crate::layout::{arrange, Layout};
 use crate::logs::*;
+use crate::rules::forget_placed;
+use crate::scratchpad::Hook;
+use crate::supervisor::{respawn, run_hook, supervisor};
 use crate::utils::*;
 use hyprland::data::{Client, Clients, Workspace};
-use crate::dispatchers::dispatchers;
 use hyprland::dispatch::WindowIdentifier;
 use hyprland::event_listener::EventListener;
 use hyprland::prelude::*;
@@ -32,6 +38,7 @@ fn add_vanish(ev: &mut EventListener, config: ConfigMutex) {
                 .filter(|cl| cl.address == data.window_address && active.id != data.workspace_id)
                 .filter(|cl| is_known(ephemeral_titles, cl))
                 .for_each(|cl| {
+                    supervisor().expect_close(&cl.address);
                     dispatchers()
                         .close_window(WindowIdentifier::Address(cl.address.clone()))
                         .log_err(f, l);
@@ -40,37 +47,124 @@ fn add_vanish(ev: &mut EventListener, config: ConfigMutex) {
     });
 }
 
-fn add_clean(ev: &mut EventListener, config: ConfigMutex) {
+fn add_clean(ev: &mut EventListener, config: ConfigMutex, options: Arc<DaemonOptions>) {
     ev.add_workspace_changed_handler(move |_| {
+        if !options.clean() {
+            return;
+        }
+
         let (f, l) = (file!(), line!());
-        let slick_map = &config.read().unwrap_log(f, l).cache.clean_map;
-        move_floating(slick_map).log_err(f, l);
+        let conf = &config.read().unwrap_log(f, l);
+        hide_floating(conf, &conf.cache.clean_map).log_err(f, l);
 
         if let Ok(Some(ac)) = Client::get_active() {
-            if is_known_map(slick_map, &ac) {
+            if is_known_map(&conf.cache.clean_map, &ac) {
                 hide_special(&ac);
             }
         }
     });
 }
 
-fn add_spotless(ev: &mut EventListener, config: ConfigMutex) {
+fn add_spotless(ev: &mut EventListener, config: ConfigMutex, options: Arc<DaemonOptions>) {
     ev.add_active_window_changed_handler(move |_| {
+        if !options.spotless() {
+            return;
+        }
+
         if let Ok(Some(cl)) = Client::get_active() {
             let (f, l) = (file!(), line!());
             let conf = &config.read().unwrap_log(f, l);
 
             if !is_known(&conf.cache.normal_titles, &cl) {
-                move_floating(&conf.cache.spotless_map).log_err(f, l);
+                hide_floating(conf, &conf.cache.spotless_map).log_err(f, l);
+            }
+        }
+    });
+}
+
+fn add_tracking(ev: &mut EventListener, config: ConfigMutex) {
+    ev.add_window_opened_handler(move |data| {
+        let (f, l) = (file!(), line!());
+        let conf = config.read().unwrap_log(f, l);
+
+        if let Some(name) = conf.get_name(&data.window_title, &data.window_class) {
+            supervisor().track(data.window_address, &name);
+        }
+    });
+}
+
+fn add_respawn(ev: &mut EventListener, config: ConfigMutex) {
+    ev.add_window_closed_handler(move |address| {
+        let (name, expected) = match supervisor().untrack(&address) {
+            Some(closed) => closed,
+            None => return,
+        };
+
+        forget_window(&address);
+        forget_placed(&address);
+        restore_fullscreen(&name);
+        let (f, l) = (file!(), line!());
+        let mut conf = config.write().unwrap_log(f, l);
+        let sc = match conf.get(&name) {
+            Some(sc) => sc.clone(),
+            None => return,
+        };
+
+        run_hook(Hook::Close, &sc.hooks, &name, &address, "", None);
+        if sc.options.respawn && !expected {
+            respawn(name, sc);
+        } else if conf.instances.contains_key(&name) {
+            let open = Clients::get().map_or(true, |clients| {
+                clients
+                    .iter()
+                    .any(|cl| cl.address != address && sc.matches_client(cl))
+            });
+            if !open {
+                conf.remove_instance(&name);
             }
         }
     });
 }
 
+fn add_layout(ev: &mut EventListener, config: ConfigMutex, layout: Layout) {
+    ev.add_window_opened_handler(move |data| {
+        let (f, l) = (file!(), line!());
+        let titles = &config.read().unwrap_log(f, l).cache.normal_titles;
+
+        if titles.contains(&data.window_title) || titles.contains(&data.window_class) {
+            arrange(layout, titles).log_err(f, l);
+        }
+    });
+}
+
+fn add_hotplug(ev: &mut EventListener) {
+    ev.add_monitor_removed_handler(|monitor| {
+        rehome_scratchpads(&monitor).log_err(file!(), line!());
+    });
+
+    ev.add_monitor_added_handler(|data| {
+        return_scratchpads(&data.name).log_err(file!(), line!());
+    });
+
+    ev.add_workspace_added_handler(|_| snapshot_monitors().log_err(file!(), line!()));
+    ev.add_workspace_moved_handler(|_| snapshot_monitors().log_err(file!(), line!()));
+}
+
 fn add_builtin_reload(ev: &mut EventListener, config: ConfigMutex) {
     ev.add_config_reloaded_handler(move || {
         let (f, l) = (file!(), line!());
-        config.write().unwrap_log(f, l).reload(None).log_err(f, l);
+        let mut conf = config.write().unwrap_log(f, l);
+        conf.reload(None).log_err(f, l);
+        supervisor().sync(&conf).log_err(f, l);
+        register_binds(&conf).log_err(f, l);
+    });
+}
+
+fn add_rebind(ev: &mut EventListener, config: ConfigMutex) {
+    ev.add_config_reloaded_handler(move || {
+        let (f, l) = (file!(), line!());
+        let conf = config.read().unwrap_log(f, l);
+        register_binds(&conf).log_err(f, l);
     });
 }
 
@@ -79,17 +173,21 @@ fn start_events(options: Arc<DaemonOptions>, config: ConfigMutex) -> Result<()>
 
     if options.auto_reload {
         add_builtin_reload(&mut ev, config.clone());
+    } else {
+        add_rebind(&mut ev, config.clone());
     }
 
-    if options.clean {
-        add_clean(&mut ev, config.clone());
-    }
+    add_clean(&mut ev, config.clone(), options.clone());
+    add_spotless(&mut ev, config.clone(), options.clone());
 
-    if options.spotless {
-        add_spotless(&mut ev, config.clone());
+    if let Some(layout) = options.layout {
+        add_layout(&mut ev, config.clone(), layout);
     }
 
     add_vanish(&mut ev, config.clone());
+    add_tracking(&mut ev, config.clone());
+    add_respawn(&mut ev, config.clone());
+    add_hotplug(&mut ev);
     ev.start_listener()
 }
 
@@ -123,9 +221,15 @@ fn reload_on_modify(res: notify::Result<Event>, config: ConfigMutex) {
 
     match res {
         Ok(e) if e.paths.contains(&config_path) => {
+            if is_own_write(&config_path) {
+                return;
+            }
+
             if let EventKind::Modify(ModifyKind::Data(_)) = e.kind {
                 sleep(Duration::from_millis(100));
                 config_guard.reload(None).log_err(f, l);
+                supervisor().sync(&config_guard).log_err(f, l);
+                register_binds(&config_guard).log_err(f, l);
             }
         }
         Err(err) => {
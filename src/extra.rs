@@ -81,9 +81,9 @@ fn max_len(xs: &[&str], min: usize, max: usize) -> usize {
         .min(max)
 }
 
-fn get_config_data(socket: Option<&str>) -> Result<String> {
+fn get_daemon_data(socket: Option<&str>, req: &str) -> Result<String> {
     let mut stream = UnixStream::connect(socket.unwrap_or(DEFAULT_SOCKET))?;
-    stream.write_all("get-config?".as_bytes())?;
+    stream.write_all(format!("{req}?").as_bytes())?;
 
     let mut buf = String::new();
     stream.read_to_string(&mut buf)?;
@@ -91,10 +91,10 @@ fn get_config_data(socket: Option<&str>) -> Result<String> {
 }
 
 fn print_group_table(group_data: &[Vec<&str>]) {
-    if group_data.len() < 2 {
+    if group_data.len() < 5 {
         return;
     }
-    let [names, scratchpadss] = &group_data[0..2] else {
+    let [names, scratchpadss, groups, options, rules] = &group_data[0..5] else {
         return;
     };
 
@@ -102,14 +102,26 @@ fn print_group_table(group_data: &[Vec<&str>]) {
     let field_widths = vec![
         max_len(names, 5, max_chars),
         max_len(scratchpadss, 11, max_chars),
+        max_len(groups, 6, max_chars),
+        max_len(options, 7, max_chars),
+        max_len(rules, 5, max_chars),
     ];
 
     print_table_outline(('┌', '┬', '┐'), &field_widths);
-    print_table_row(&["Group", "Scratchpads"], &field_widths);
+    print_table_row(
+        &["Group", "Scratchpads", "Nested", "Options", "Rules"],
+        &field_widths,
+    );
 
     print_table_outline(('├', '┼', '┤'), &field_widths);
-    for (name, scratchpads) in names.iter().zip(scratchpadss) {
-        print_table_row(&[name, scratchpads], &field_widths);
+    for ((((name, scratchpads), groups), options), rules) in names
+        .iter()
+        .zip(scratchpadss)
+        .zip(groups)
+        .zip(options)
+        .zip(rules)
+    {
+        print_table_row(&[name, scratchpads, groups, options, rules], &field_widths);
     }
     print_table_outline(('└', '┴', '┘'), &field_widths);
 }
@@ -121,7 +133,7 @@ fn get_centered_conf(conf: &str, width: usize) -> String {
         conf
     };
 
-    let center_fix = if width % 2 == 0 {
+    let center_fix = if width.is_multiple_of(2) {
         config.len() + config.len() % 2
     } else {
         config.len() - 1
@@ -220,8 +232,8 @@ fn parse_data(data: &str, field_num: usize) -> Vec<Vec<&str>> {
     parsed_data
 }
 
-fn parse_config_data(data: &str) -> ParsedConfig {
-    let (sc_fields, g_fields) = (5, 2);
+fn parse_config_data(data: &str) -> ParsedConfig<'_> {
+    let (sc_fields, g_fields) = (5, 5);
     match data.splitn(3, '\u{2C00}').collect::<Vec<_>>()[..] {
         [c, scd, gd] => (c, parse_data(scd, sc_fields), parse_data(gd, g_fields)),
         [c, scd] => (c, parse_data(scd, sc_fields), vec![]),
@@ -233,7 +245,7 @@ fn parse_config_data(data: &str) -> ParsedConfig {
 }
 
 pub fn print_config(socket: Option<&str>, raw: bool) -> Result<()> {
-    let data = get_config_data(socket)?;
+    let data = get_daemon_data(socket, "get-config")?;
     let parsed_data = parse_config_data(&data);
 
     if raw {
@@ -245,6 +257,12 @@ pub fn print_config(socket: Option<&str>, raw: bool) -> Result<()> {
     Ok(())
 }
 
+pub fn print_daemon_reply(socket: Option<&str>, req: &str) -> Result<()> {
+    let data = get_daemon_data(socket, req)?;
+    println!("{}", data.trim_end());
+    Ok(())
+}
+
 fn get_log_data() -> Result<String> {
     let mut file = File::open(DEFAULT_LOGFILE)?;
     let mut buf = String::new();
@@ -352,6 +370,7 @@ DAEMON OPTIONS
   clean                      Hide scratchpads on workspace change
   spotless                   Hide scratchpads on focus change
   eager                      Spawn scratchpads hidden on start
+  layout <mode>              Arrange visible floating scratchpads: side-by-side, grid or cascade
   no-auto-reload             Don't reload the configuration when the configuration file is updated
   config </path/to/config>   Specify a path to the configuration file
 
@@ -362,24 +381,44 @@ SCRATCHPAD OPTIONS
   sticky                     Prevent the scratchpad from being hidden by 'clean'
   shiny                      Prevent the scratchpad from being hidden by 'spotless'
   lazy                       Prevent the scratchpad from being spawned by 'eager'
+  respawn                    Spawn the scratchpad again hidden when its window is closed
   show                       Only creates or brings up the scratchpad
   hide                       Only hides the scratchpad
   poly                       Toggle all scratchpads matching the title simultaneously
   pin                        Keep the scratchpad active through workspace changes
   tiled                      Makes a tiled scratchpad instead of a floating one
-  monitor <id|name>          Restrict the scratchpad to a specified monitor
+  per_workspace              Use a separate instance of the scratchpad on every workspace
+  inherit_cwd                Spawn the scratchpad in the working directory of the focused window, see also '{{cwd}}'
+  monitor <id|name>[,...]    Restrict the scratchpad to the first available monitor, also accepts 'focused' and 'cursor'
+  fullscreen <policy>        What to do over a fullscreen window: 'unset' it until hidden, show 'over' it or 'refuse'
+  only_on_workspace <pat>    Only allow the scratchpad on workspaces matching the id or pattern
+  only_on_monitor <pat>      Only allow the scratchpad on monitors matching the id or pattern
   group <name>               Add the scratchpad to the specified group
   special                    Use Hyprland's special workspace, ignores most other options
 
 EXTRA COMMANDS
   cycle [normal|special]     Cycle between [only normal | only special] scratchpads
+        [group:<name>]       Only cycle through the scratchpads of a group
+        [--reverse]          Cycle backwards
+        [--mru]              Cycle in most recently used order
+        [--monitor]          Only cycle through scratchpads shown on the focused monitor
   toggle <name>              Toggles the scratchpad with the given name
+         [--arg key=value]   Fill '{{arg:key}}' placeholders, extra positional arguments fill '{{1}}', '{{2}}'...
   show <name>                Shows the scratchpad with the given name
   hide <name>                Hides the scratchpad with the given name
-  previous [show|hide]       Spawn the previous non-active scratchpad
+  profile <name>             Switch to a profile, hiding or closing the scratchpads outside of it
+  swap <name>                Replace the focused scratchpad with another one in the same place
+  previous [N] [show|hide]   Spawn the Nth previous non-active scratchpad
+  history                    Print the recently used scratchpads, most recent first
   menu [fzf|rofi]            Spawn a menu to search through and trigger scratchpads.
-  hide-all                   Hide all scratchpads
+  hide-all [--toggle]        Hide all scratchpads, or restore them if '--toggle' is given and they are hidden
+  restore-all                Bring back the scratchpads hidden by the last 'hide-all'
   kill-all                   Close all scratchpads
+  attach [--name <name>]     Make the focused window a scratchpad
+         [--options <opts>]  Scratchpad options of the attached window
+         [--save]            Also append it to the configuration file
+  detach [name]              Turn a scratchpad or the focused one back into a normal window
+  status                     Print the runtime state of the daemon
   reload (-r) [config]       Update the config file
   get-config (-g)            Print parsed config file
   kill (-k)                  Kill the hyprscratch daemon
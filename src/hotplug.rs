@@ -0,0 +1,204 @@
+use crate::dispatchers::dispatchers;
+use crate::logs::*;
+use crate::supervisor::supervisor;
+use crate::utils::*;
+use hyprland::data::{Client, Clients, Monitor, Monitors, Workspace, Workspaces};
+use hyprland::dispatch::{WindowIdentifier, WorkspaceIdentifierWithSpecial};
+use hyprland::prelude::*;
+use hyprland::shared::{Address, MonitorId, WorkspaceId};
+use hyprland::Result;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+static HOTPLUG: OnceLock<Mutex<Hotplug>> = OnceLock::new();
+
+fn hotplug() -> MutexGuard<'static, Hotplug> {
+    HOTPLUG
+        .get_or_init(|| Mutex::new(Hotplug::default()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Output {
+    id: MonitorId,
+    geometry: (i32, i32, i32, i32),
+    workspaces: Vec<WorkspaceId>,
+}
+
+#[derive(Default)]
+struct Hotplug {
+    outputs: HashMap<String, Output>,
+    displaced: HashMap<Address, (String, (i32, i32))>,
+    unplugged: HashSet<String>,
+}
+
+impl Hotplug {
+    fn sync_monitors(&mut self, monitors: &[Monitor], workspaces: &[Workspace]) {
+        self.outputs = monitors
+            .iter()
+            .map(|m| {
+                let output = Output {
+                    id: m.id,
+                    geometry: monitor_geometry(m),
+                    workspaces: workspaces
+                        .iter()
+                        .filter(|ws| ws.monitor == m.name)
+                        .map(|ws| ws.id)
+                        .collect(),
+                };
+                (m.name.clone(), output)
+            })
+            .collect();
+    }
+
+    fn unplug(&mut self, monitor: &str) -> Option<Output> {
+        self.unplugged.insert(monitor.into());
+        self.outputs.remove(monitor)
+    }
+
+    fn displace(&mut self, address: Address, monitor: &str, offset: (i32, i32)) {
+        self.displaced
+            .entry(address)
+            .or_insert_with(|| (monitor.into(), offset));
+    }
+
+    fn replug(&mut self, monitor: &str) -> HashMap<Address, (i32, i32)> {
+        self.unplugged.remove(monitor);
+        let (returning, remaining) = self
+            .displaced
+            .drain()
+            .partition::<HashMap<_, _>, _>(|(_, (m, _))| m == monitor);
+
+        self.displaced = remaining;
+        returning
+            .into_iter()
+            .map(|(address, (_, offset))| (address, offset))
+            .collect()
+    }
+}
+
+pub fn is_unplugged(monitor: &str) -> bool {
+    hotplug().unplugged.contains(monitor)
+}
+
+pub fn forget_window(address: &Address) {
+    hotplug().displaced.remove(address);
+}
+
+pub fn snapshot_monitors() -> Result<()> {
+    let monitors = Monitors::get()?.into_iter().collect::<Vec<_>>();
+    let workspaces = Workspaces::get()?.into_iter().collect::<Vec<_>>();
+    hotplug().sync_monitors(&monitors, &workspaces);
+    Ok(())
+}
+
+fn place_window(cl: &Client, monitor: &Monitor, (dx, dy): (i32, i32)) {
+    let win = || WindowIdentifier::Address(cl.address.clone());
+    let workspace = WorkspaceIdentifierWithSpecial::Id(monitor.active_workspace.id);
+    dispatchers()
+        .move_to_workspace_silent(workspace, Some(win()))
+        .log_err(file!(), line!());
+
+    if cl.floating {
+        let geometry = monitor_geometry(monitor);
+        let size = (cl.size.0.into(), cl.size.1.into());
+        let at = clamp_position(geometry, (geometry.0 + dx, geometry.1 + dy), size);
+        dispatchers()
+            .move_window_pixel(at, win())
+            .log_err(file!(), line!());
+    }
+}
+
+fn offset_from(cl: &Client, (x, y): (i32, i32)) -> (i32, i32) {
+    (i32::from(cl.at.0) - x, i32::from(cl.at.1) - y)
+}
+
+pub fn rehome_scratchpads(monitor: &str) -> Result<()> {
+    let old = hotplug().unplug(monitor);
+    let monitors = Monitors::get()?
+        .into_iter()
+        .filter(|m| m.name != monitor && !m.disabled)
+        .collect::<Vec<_>>();
+
+    let fallback = monitors.iter().find(|m| m.focused).or(monitors.first());
+    let (old, fallback) = match (old, fallback) {
+        (Some(old), Some(fallback)) => (old, fallback),
+        _ => return snapshot_monitors(),
+    };
+
+    for cl in Clients::get()? {
+        let stranded = cl.monitor == Some(old.id) || old.workspaces.contains(&cl.workspace.id);
+        let name = supervisor().name_of(&cl.address);
+        let name = match name {
+            Some(name) if stranded && !is_on_special(&cl) => name,
+            _ => continue,
+        };
+
+        let origin = match monitors.iter().find(|m| Some(m.id) == cl.monitor) {
+            Some(current) => (current.x, current.y),
+            None => (old.geometry.0, old.geometry.1),
+        };
+
+        let offset = offset_from(&cl, origin);
+        place_window(&cl, fallback, offset);
+        hotplug().displace(cl.address.clone(), monitor, offset);
+
+        let msg = format!("Moved scratchpad '{name}' to {}", fallback.name);
+        log(msg, Info)?;
+    }
+    snapshot_monitors()
+}
+
+pub fn return_scratchpads(monitor: &str) -> Result<()> {
+    snapshot_monitors()?;
+    let monitors = Monitors::get()?.into_iter().collect::<Vec<_>>();
+    let returning = hotplug().replug(monitor);
+    let target = match monitors.iter().find(|m| m.name == monitor) {
+        Some(target) => target,
+        None => return Ok(()),
+    };
+
+    for cl in Clients::get()? {
+        let offset = match returning.get(&cl.address) {
+            Some(offset) if !is_on_special(&cl) && cl.monitor != Some(target.id) => *offset,
+            _ => continue,
+        };
+
+        let name = supervisor().name_of(&cl.address).unwrap_or_default();
+        place_window(&cl, target, offset);
+        log(format!("Returned scratchpad '{name}' to {monitor}"), Info)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hotplug() {
+        let mut hotplug = Hotplug::default();
+        let (term, notes) = (Address::new("1"), Address::new("2"));
+        let output = Output {
+            id: 1,
+            geometry: (1920, 0, 2560, 1440),
+            workspaces: vec![2, 5],
+        };
+        hotplug.outputs.insert("DP-2".into(), output.clone());
+
+        assert!(!hotplug.unplugged.contains("DP-2"));
+        assert_eq!(hotplug.unplug("DP-2"), Some(output));
+        assert!(hotplug.unplugged.contains("DP-2"));
+
+        hotplug.displace(term.clone(), "DP-2", (100, 50));
+        hotplug.displace(notes.clone(), "HDMI-A-1", (0, 0));
+        hotplug.displace(term.clone(), "HDMI-A-1", (0, 0));
+
+        assert_eq!(hotplug.replug("DP-2"), HashMap::from([(term, (100, 50))]));
+        assert!(!hotplug.unplugged.contains("DP-2"));
+
+        hotplug.displaced.remove(&notes);
+        assert_eq!(hotplug.replug("HDMI-A-1"), HashMap::new());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::dispatchers::dispatchers;
+use crate::logs::*;
+use crate::utils::*;
+use hyprland::data::{Client, Clients, Monitor};
+use hyprland::dispatch::WindowIdentifier;
+use hyprland::prelude::*;
+use hyprland::Result;
+
+type Rect = (i32, i32, i32, i32);
+
+const GAP: i32 = 10;
+const CASCADE_STEP: i32 = 40;
+const CASCADE_SIZE: f32 = 0.6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    SideBySide,
+    Grid,
+    Cascade,
+}
+
+impl Layout {
+    pub fn new(name: &str) -> Option<Layout> {
+        match name {
+            "side-by-side" => Some(Layout::SideBySide),
+            "grid" => Some(Layout::Grid),
+            "cascade" => Some(Layout::Cascade),
+            _ => {
+                let _ = log(format!("Unknown layout: {name}"), Warn);
+                None
+            }
+        }
+    }
+
+    fn compute(&self, area: Rect, count: usize) -> Vec<Rect> {
+        if count == 0 {
+            return vec![];
+        }
+
+        match self {
+            Layout::SideBySide => grid(area, count, 1),
+            Layout::Grid => {
+                let cols = (count as f32).sqrt().ceil() as usize;
+                grid(area, cols, count.div_ceil(cols))
+            }
+            Layout::Cascade => cascade(area, count),
+        }
+        .into_iter()
+        .take(count)
+        .collect()
+    }
+}
+
+fn grid((x, y, w, h): Rect, cols: usize, rows: usize) -> Vec<Rect> {
+    let (cols, rows) = (cols as i32, rows as i32);
+    let cell_w = (w - GAP * (cols + 1)) / cols;
+    let cell_h = (h - GAP * (rows + 1)) / rows;
+
+    (0..rows)
+        .flat_map(|r| (0..cols).map(move |c| (r, c)))
+        .map(|(r, c)| {
+            (
+                x + GAP + c * (cell_w + GAP),
+                y + GAP + r * (cell_h + GAP),
+                cell_w,
+                cell_h,
+            )
+        })
+        .collect()
+}
+
+fn cascade((x, y, w, h): Rect, count: usize) -> Vec<Rect> {
+    let (win_w, win_h) = (
+        (w as f32 * CASCADE_SIZE) as i32,
+        (h as f32 * CASCADE_SIZE) as i32,
+    );
+    let max_steps = ((w - win_w).min(h - win_h) / CASCADE_STEP).max(1);
+
+    (0..count as i32)
+        .map(|i| {
+            let offset = GAP + (i % max_steps) * CASCADE_STEP;
+            (x + offset, y + offset, win_w, win_h)
+        })
+        .collect()
+}
+
+fn usable_area(monitor: &Monitor) -> Rect {
+    let (x, y, w, h) = monitor_geometry(monitor);
+    let (left, top, right, bottom) = monitor.reserved;
+    let (left, top, right, bottom) = (
+        i32::from(left),
+        i32::from(top),
+        i32::from(right),
+        i32::from(bottom),
+    );
+    (x + left, y + top, w - left - right, h - top - bottom)
+}
+
+fn place(client: &Client, (x, y, w, h): Rect) -> Result<()> {
+    let win = || WindowIdentifier::Address(client.address.clone());
+    dispatchers().resize_window_pixel((w, h), win())?;
+    dispatchers().move_window_pixel((x, y), win())
+}
+
+pub fn arrange(layout: Layout, titles: &[String]) -> Result<()> {
+    let monitor = Monitor::get_active()?;
+    let mut clients: Vec<Client> = Clients::get()?
+        .into_iter()
+        .filter(|cl| cl.floating && cl.workspace.id == monitor.active_workspace.id)
+        .filter(|cl| is_known(titles, cl))
+        .collect();
+
+    let order = |cl: &Client| {
+        titles
+            .iter()
+            .position(|t| *t == cl.initial_title || *t == cl.initial_class)
+    };
+    clients.sort_by_key(order);
+
+    let rects = layout.compute(usable_area(&monitor), clients.len());
+    for (client, rect) in clients.iter().zip(rects) {
+        place(client, rect)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = (0, 0, 1930, 1090);
+
+    #[test]
+    fn test_side_by_side() {
+        assert_eq!(Layout::SideBySide.compute(AREA, 0), vec![]);
+        assert_eq!(
+            Layout::SideBySide.compute(AREA, 1),
+            vec![(10, 10, 1910, 1070)]
+        );
+        assert_eq!(
+            Layout::SideBySide.compute(AREA, 2),
+            vec![(10, 10, 950, 1070), (970, 10, 950, 1070)]
+        );
+    }
+
+    #[test]
+    fn test_grid() {
+        assert_eq!(
+            Layout::Grid.compute(AREA, 3),
+            vec![(10, 10, 950, 530), (970, 10, 950, 530), (10, 550, 950, 530)]
+        );
+        assert_eq!(Layout::Grid.compute(AREA, 5).len(), 5);
+        assert_eq!(Layout::Grid.compute(AREA, 5)[2], (1290, 10, 630, 530));
+    }
+
+    #[test]
+    fn test_cascade() {
+        let rects = Layout::Cascade.compute((100, 0, 1000, 1000), 3);
+        assert_eq!(
+            rects,
+            vec![
+                (110, 10, 600, 600),
+                (150, 50, 600, 600),
+                (190, 90, 600, 600)
+            ]
+        );
+    }
+}
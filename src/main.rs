@@ -1,10 +1,15 @@
+mod binds;
 mod config;
 mod daemon;
 mod dispatchers;
 mod event;
 mod extra;
+mod hotplug;
+mod layout;
 mod logs;
+mod rules;
 mod scratchpad;
+mod supervisor;
 mod utils;
 
 use crate::daemon::initialize_daemon;
@@ -37,22 +42,31 @@ const KNOWN_CLI_COMMANDS: [&str; 7] = [
     "kill",
 ];
 
-const KNOWN_COMMANDS: [&str; 20] = [
+const CYCLE_FLAGS: [&str; 3] = ["--reverse", "--monitor", "--mru"];
+const REQUEST_FLAGS: [&str; 5] = ["--arg", "--name", "--options", "--save", "--toggle"];
+
+const KNOWN_COMMANDS: [&str; 26] = [
     "no-auto-reload",
+    "restore-all",
     "get-config",
     "spotless",
     "hide-all",
     "kill-all",
     "previous",
+    "profile",
+    "history",
     "version",
     "attach",
+    "detach",
     "reload",
     "toggle",
+    "status",
     "clean",
     "eager",
     "cycle",
     "init",
     "show",
+    "swap",
     "hide",
     "menu",
     "kill",
@@ -77,7 +91,10 @@ fn get_cli_command(args: &[String]) -> Option<&str> {
     for arg in args {
         if let Some(flag) = get_flag_name(arg) {
             return Some(flag);
-        } else if arg.starts_with('-') {
+        } else if arg.starts_with('-')
+            && !CYCLE_FLAGS.contains(&arg.as_str())
+            && !REQUEST_FLAGS.contains(&arg.split('=').next().unwrap_or_default())
+        {
             let _ = log(format!("Unknown flag: {arg}"), Warn);
         }
     }
@@ -96,12 +113,53 @@ fn send_manual(args: &[String], socket: Option<&str>) -> Result<()> {
     send_request(socket, "manual", &args[1..].join("^"))
 }
 
+fn get_call_msg(args: &[String]) -> String {
+    let mut msg = args.get(2).cloned().unwrap_or_default();
+    let mut position = 0;
+    let mut rest = args.iter().skip(3);
+    while let Some(arg) = rest.next() {
+        let named = match arg.as_str() {
+            "--arg" => rest.next().cloned(),
+            a if a.starts_with("--arg=") => Some(a["--arg=".len()..].to_string()),
+            "--config" | "-c" | "--socket" | "-s" => {
+                rest.next();
+                None
+            }
+            a if a.starts_with('-') => None,
+            a => {
+                position += 1;
+                Some(format!("{position}={a}"))
+            }
+        };
+
+        if let Some(named) = named.filter(|n| n.contains('=')) {
+            msg += &format!("^{named}");
+        }
+    }
+    msg
+}
+
+fn get_attach_msg(args: &[String]) -> String {
+    let options = match args.get(2) {
+        Some(opts) if !opts.starts_with('-') => Some(opts.clone()),
+        _ => get_flag_arg(args, "options"),
+    };
+    let name = get_flag_arg(args, "name").unwrap_or_default();
+    let save = args.iter().any(|a| a == "--save");
+    format!("{name}^{}^{save}", options.unwrap_or_default())
+}
+
 fn exec_main_command(args: &[String], config: Option<String>, socket: Option<&str>) -> Result<()> {
     let get_arg = |i| args.get(i).map_or("", |x: &String| x.as_str());
     let (req, msg) = (get_arg(1), get_arg(2));
     match req {
         "init" => initialize_daemon(args.join(" "), config, socket),
         "menu" => menu(socket, msg, get_arg(3))?,
+        "status" | "history" => print_daemon_reply(socket, req)?,
+        "cycle" => send_request(socket, req, &args[2..].join(" "))?,
+        "toggle" | "show" | "hide" => send_request(socket, req, &get_call_msg(args))?,
+        "attach" => send_request(socket, req, &get_attach_msg(args))?,
+        "previous" => send_request(socket, req, &format!("{msg} {}", get_arg(3)))?,
         "" => print_help(),
         _ if KNOWN_COMMANDS.contains(&req) => send_request(socket, req, msg)?,
         _ => send_manual(args, socket)?,
@@ -0,0 +1,289 @@
+use crate::dispatchers::dispatchers;
+use crate::logs::*;
+use crate::scratchpad::Scratchpad;
+use crate::utils::*;
+use hyprland::data::{Client, Monitor, Monitors};
+use hyprland::dispatch::WindowIdentifier;
+use hyprland::prelude::*;
+use hyprland::shared::{Address, MonitorId};
+use hyprland::Result;
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+static PLACED: OnceLock<Mutex<HashSet<Address>>> = OnceLock::new();
+
+fn placed() -> MutexGuard<'static, HashSet<Address>> {
+    PLACED
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+pub fn mark_placed(address: &Address) -> bool {
+    placed().insert(address.clone())
+}
+
+pub fn forget_placed(address: &Address) {
+    placed().remove(address);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Rule {
+    Float,
+    Size(String, String),
+    Move(String, String),
+    Opacity(String, Option<String>),
+    Pin,
+    NoBorder,
+}
+
+impl Rule {
+    fn parse(rule: &str) -> Option<Rule> {
+        let args = rule.split_whitespace().collect::<Vec<_>>();
+        match args[..] {
+            ["float"] => Some(Rule::Float),
+            ["size", w, h] => Some(Rule::Size(w.into(), h.into())),
+            ["move", x, y] => Some(Rule::Move(x.into(), y.into())),
+            ["opacity", active] => Some(Rule::Opacity(active.into(), None)),
+            ["opacity", active, inactive, ..] => {
+                Some(Rule::Opacity(active.into(), Some(inactive.into())))
+            }
+            ["pin"] => Some(Rule::Pin),
+            ["noborder"] => Some(Rule::NoBorder),
+            _ => None,
+        }
+    }
+}
+
+fn parse_rules(rules: &str) -> Vec<Rule> {
+    rules.split(';').filter_map(Rule::parse).collect()
+}
+
+fn scratchpad_rules(sc: &Scratchpad) -> String {
+    let first = sc.command.split('?').next().unwrap_or_default().trim();
+    let inline = first
+        .strip_prefix('[')
+        .and_then(|cmd| cmd.split_once(']'))
+        .map_or("", |(rules, _)| rules);
+    format!("{};{inline}", sc.rules)
+}
+
+fn eval_factor(factor: &str, vars: &[(&str, f64)], percent_of: f64) -> Option<f64> {
+    let factor = factor.trim();
+    if let Some(percent) = factor.strip_suffix('%') {
+        return Some(percent.trim().parse::<f64>().ok()? * percent_of / 100.0);
+    }
+
+    match vars.iter().find(|(name, _)| *name == factor) {
+        Some((_, value)) => Some(*value),
+        None => factor.parse().ok(),
+    }
+}
+
+fn eval_term(term: &str, vars: &[(&str, f64)], percent_of: f64) -> Option<f64> {
+    let mut value = 1.0;
+    let mut op = '*';
+    let mut start = 0;
+
+    for (i, c) in term.char_indices().chain([(term.len(), '*')]) {
+        if c != '*' && c != '/' {
+            continue;
+        }
+
+        let factor = eval_factor(&term[start..i], vars, percent_of)?;
+        value = match op {
+            '*' => value * factor,
+            _ => value / factor,
+        };
+        (op, start) = (c, i + 1);
+    }
+    Some(value)
+}
+
+fn eval_expr(expr: &str, vars: &[(&str, f64)], percent_of: f64) -> Option<f64> {
+    let (mut total, mut sign, mut term) = (0.0, 1.0, String::new());
+    let mut leading = true;
+    for c in expr.trim().chars() {
+        let operand = term.trim_end();
+        if (c != '+' && c != '-') || operand.ends_with(['*', '/']) {
+            term.push(c);
+        } else if operand.is_empty() {
+            // A sign without an operand is only allowed at the start, so `10--5` is rejected
+            if !leading {
+                return None;
+            }
+            term.push(c);
+        } else {
+            total += sign * eval_term(&term, vars, percent_of)?;
+            sign = if c == '-' { -1.0 } else { 1.0 };
+            term.clear();
+            leading = false;
+        }
+    }
+
+    let total = total + sign * eval_term(&term, vars, percent_of)?;
+    total.is_finite().then_some(total)
+}
+
+fn target_monitor(monitor: Option<MonitorId>) -> Result<Monitor> {
+    let monitors = Monitors::get()?.into_iter().collect::<Vec<_>>();
+    let target = monitors.iter().find(|m| Some(m.id) == monitor);
+    match target.or_else(|| monitors.iter().find(|m| m.focused)) {
+        Some(m) => Ok(m.clone()),
+        None => Monitor::get_active(),
+    }
+}
+
+fn resolve_pair(
+    (x, y): (&str, &str),
+    (mw, mh): (i32, i32),
+    (ww, wh): (i32, i32),
+) -> Option<(i32, i32)> {
+    let vars = [
+        ("monitor_w", f64::from(mw)),
+        ("monitor_h", f64::from(mh)),
+        ("window_w", f64::from(ww)),
+        ("window_h", f64::from(wh)),
+    ];
+
+    let x = eval_expr(x, &vars, f64::from(mw))?;
+    let y = eval_expr(y, &vars, f64::from(mh))?;
+    Some((x.round() as i32, y.round() as i32))
+}
+
+pub fn apply_rules(sc: &Scratchpad, cl: &Client, monitor: Option<MonitorId>, on_special: bool) {
+    let rules = parse_rules(&scratchpad_rules(sc));
+    if rules.is_empty() {
+        return;
+    }
+
+    let monitor = match target_monitor(monitor) {
+        Ok(m) => m,
+        Err(e) => {
+            let msg = format!("Could not apply rules to '{}': {e}", sc.title);
+            let _ = log(msg, Warn);
+            return;
+        }
+    };
+
+    // Geometry is only set the first time a window is shown, leaving manual moves alone
+    let placed = !mark_placed(&cl.address);
+    let (mx, my, mw, mh) = monitor_geometry(&monitor);
+    let mut size = (i32::from(cl.size.0), i32::from(cl.size.1));
+    let win = || WindowIdentifier::Address(cl.address.clone());
+    let (f, l) = (file!(), line!());
+    let invalid = |rule: String| {
+        let msg = format!("Could not evaluate '{rule}' in the rules of '{}'", sc.title);
+        let _ = log(msg, Warn);
+    };
+
+    for rule in rules {
+        match rule {
+            Rule::Float if !cl.floating => dispatchers().set_floating(win()).log_err(f, l),
+            Rule::Size(..) | Rule::Move(..) if placed => (),
+            Rule::Size(w, h) => match resolve_pair((&w, &h), (mw, mh), size) {
+                Some(new_size) => {
+                    size = new_size;
+                    dispatchers().resize_window_pixel(size, win()).log_err(f, l);
+                }
+                None => invalid(format!("size {w} {h}")),
+            },
+            Rule::Move(x, y) => match resolve_pair((&x, &y), (mw, mh), size) {
+                Some((x, y)) => dispatchers()
+                    .move_window_pixel((mx + x, my + y), win())
+                    .log_err(f, l),
+                None => invalid(format!("move {x} {y}")),
+            },
+            Rule::Opacity(active, inactive) => {
+                dispatchers()
+                    .set_prop("alpha", &active, win())
+                    .log_err(f, l);
+                if let Some(inactive) = inactive {
+                    dispatchers()
+                        .set_prop("alphainactive", &inactive, win())
+                        .log_err(f, l);
+                }
+            }
+            Rule::Pin if !cl.pinned && !on_special && !sc.options.pin => {
+                dispatchers().toggle_pin_window(win()).log_err(f, l)
+            }
+            Rule::NoBorder => dispatchers().set_prop("noborder", "1", win()).log_err(f, l),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = "float; size monitor_w*0.7 80%; workspace 2; move 10 10; opacity 0.9 0.8; pin";
+        assert_eq!(
+            parse_rules(rules),
+            vec![
+                Rule::Float,
+                Rule::Size("monitor_w*0.7".into(), "80%".into()),
+                Rule::Move("10".into(), "10".into()),
+                Rule::Opacity("0.9".into(), Some("0.8".into())),
+                Rule::Pin,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scratchpad_rules() {
+        let mut sc = Scratchpad::new("term", "[noborder] kitty?[pin] foot", "float", "");
+        assert_eq!(scratchpad_rules(&sc), "float;noborder");
+
+        sc.command = "kitty".into();
+        assert_eq!(scratchpad_rules(&sc), "float;");
+    }
+
+    #[test]
+    fn test_mark_placed() {
+        let address = Address::new("test_placed");
+        assert!(mark_placed(&address));
+        assert!(!mark_placed(&address));
+
+        forget_placed(&address);
+        assert!(mark_placed(&address));
+    }
+
+    #[test]
+    fn test_eval_expr() {
+        let vars = [("monitor_w", 1920.0), ("window_w", 800.0)];
+        let cases = [
+            ("100", Some(100.0)),
+            ("-20", Some(-20.0)),
+            ("50%", Some(960.0)),
+            ("monitor_w*0.5", Some(960.0)),
+            ("monitor_w/2-window_w/2", Some(560.0)),
+            ("100%-window_w-20", Some(1100.0)),
+            ("monitor_w*-0.25", Some(-480.0)),
+            ("cursor_x", None),
+            ("", None),
+            ("10--5", None),
+            ("--5", None),
+            ("10+-5", None),
+            ("monitor_w*--0.25", None),
+            ("10-", None),
+            ("-", None),
+            ("*5", None),
+            ("5**2", None),
+            ("5/", None),
+            ("%", None),
+            ("100/0", None),
+            ("inf", None),
+            ("(100", None),
+            ("100)", None),
+            ("(monitor_w/2", None),
+            ("(100)", None),
+        ];
+
+        for (expr, expected) in cases {
+            assert_eq!(eval_expr(expr, &vars, 1920.0), expected, "{expr}");
+        }
+    }
+}
@@ -1,13 +1,20 @@
+use crate::config::Config;
 use crate::dispatchers::dispatchers;
+use crate::hotplug::is_unplugged;
 use crate::logs::*;
+use crate::rules::apply_rules;
+use crate::supervisor::{run_client_hook, run_config_hook, run_hook, spawn_tracked};
 use crate::utils::*;
-use hyprland::data::{Client, Clients, Monitors, Workspace};
+use hyprland::data::{Client, Clients, CursorPosition, FullscreenMode, Monitors, Workspace};
 use hyprland::dispatch::{WindowIdentifier, WorkspaceIdentifierWithSpecial};
 use hyprland::prelude::*;
+use hyprland::shared::MonitorId;
 use hyprland::Result;
 use std::collections::HashMap;
+use std::time::Duration;
 
 struct HyprlandState {
+    name: String,
     clients_with_title: Vec<Client>,
     special_workspace: String,
     active_workspace: Workspace,
@@ -15,24 +22,59 @@ struct HyprlandState {
     monitors: HashMap<String, String>,
 }
 
+pub fn get_monitors(monitor: Option<&String>) -> Result<HashMap<String, String>> {
+    let cursor = match monitor {
+        Some(m) if m.contains("cursor") => CursorPosition::get().ok(),
+        _ => None,
+    };
+
+    let mut monitors = HashMap::new();
+    for m in Monitors::get()? {
+        let workspace = m.active_workspace.name.clone();
+        if m.focused {
+            monitors.insert("focused".into(), workspace.clone());
+        }
+
+        if let Some(c) = &cursor {
+            if contains_point(monitor_geometry(&m), (c.x, c.y)) {
+                monitors.insert("cursor".into(), workspace.clone());
+            }
+        }
+
+        monitors.insert(m.id.to_string(), workspace.clone());
+        monitors.insert(m.name, workspace);
+    }
+    Ok(monitors)
+}
+
+pub fn resolve_monitor(monitors: &HashMap<String, String>, spec: &str) -> Option<String> {
+    spec.split(',')
+        .map(str::trim)
+        .find_map(|m| monitors.get(m).cloned())
+}
+
 impl HyprlandState {
-    fn new(title: &str, name: &str) -> Result<HyprlandState> {
-        let mut monitors = HashMap::new();
-        Monitors::get()?.into_iter().for_each(|x| {
-            monitors.insert(x.name.clone(), x.active_workspace.name.clone());
-            monitors.insert(x.id.to_string(), x.active_workspace.name.clone());
-        });
+    fn new(sc: &Scratchpad, name: &str) -> Result<HyprlandState> {
+        let monitors = get_monitors(sc.options.monitor.as_ref())?;
+        let title = &sc.title;
 
         let active_workspace = Workspace::get_active()?;
-        let special_workspace = name.into();
+        let special_workspace = expand_workspace(&sc.hide_workspace(name), active_workspace.id);
+
+        let is_instance = |cl: &Client| {
+            !sc.options.per_workspace
+                || cl.workspace.id == active_workspace.id
+                || cl.workspace.name == format!("special:{special_workspace}")
+        };
 
         let active_client = Client::get_active()?;
         let clients_with_title = Clients::get()?
             .into_iter()
-            .filter(|cl| is_known(&[title.into()], cl))
+            .filter(|cl| is_known(&[title.into()], cl) && is_instance(cl))
             .collect();
 
         Ok(HyprlandState {
+            name: name.into(),
             clients_with_title,
             special_workspace,
             active_workspace,
@@ -44,14 +86,156 @@ impl HyprlandState {
     fn toggle_special(&self) -> Result<()> {
         dispatchers().toggle_special_workspace(Some(self.special_workspace.clone()))
     }
+
+    fn monitor_of(&self, workspace: &str) -> Option<MonitorId> {
+        self.monitors
+            .iter()
+            .filter(|(_, ws)| *ws == workspace)
+            .find_map(|(m, _)| m.parse().ok())
+    }
+}
+
+pub struct Location {
+    workspace: Workspace,
+}
+
+impl Location {
+    pub fn get() -> Result<Location> {
+        Ok(Location {
+            workspace: Workspace::get_active()?,
+        })
+    }
+
+    fn matches(&self, target: &str, pattern: &str) -> bool {
+        let ws = &self.workspace;
+        match target {
+            "workspace" => matches_location(pattern, &ws.id.to_string(), &ws.name),
+            "monitor" => {
+                let id = ws.monitor_id.map(|id| id.to_string()).unwrap_or_default();
+                matches_location(pattern, &id, &ws.monitor)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    Show,
+    Hide,
+    Spawn,
+    Close,
+}
+
+impl Hook {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Hook::Show => "show",
+            Hook::Hide => "hide",
+            Hook::Spawn => "spawn",
+            Hook::Close => "close",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hooks {
+    pub on_show: String,
+    pub on_hide: String,
+    pub on_spawn: String,
+    pub on_close: String,
+}
+
+impl Hooks {
+    pub fn get(&self, hook: Hook) -> &str {
+        match hook {
+            Hook::Show => &self.on_show,
+            Hook::Hide => &self.on_hide,
+            Hook::Spawn => &self.on_spawn,
+            Hook::Close => &self.on_close,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Step {
+    pub after: Option<String>,
+    pub delay: Duration,
+}
+
+impl Step {
+    pub fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "after" => self.after = Some(value.into()),
+            "delay" => match parse_duration(value) {
+                Some(delay) => self.delay = delay,
+                None => {
+                    let _ = log(format!("Invalid delay '{value}'"), Warn);
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    target: String,
+    pattern: String,
+    name: String,
+}
+
+impl Condition {
+    fn parse(when: &str) -> Option<Condition> {
+        match when.split_whitespace().collect::<Vec<_>>()[..] {
+            [target @ ("workspace" | "monitor"), pattern, name] => {
+                check_pattern(pattern).then(|| Condition {
+                    target: target.into(),
+                    pattern: pattern.into(),
+                    name: name.into(),
+                })
+            }
+            _ => {
+                let _ = log(format!("Invalid 'when' clause: '{when}'"), Warn);
+                None
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FullscreenPolicy {
+    Unset,
+    Over,
+    Refuse,
+}
+
+impl FullscreenPolicy {
+    fn new(name: &str) -> Option<FullscreenPolicy> {
+        match name {
+            "unset" => Some(FullscreenPolicy::Unset),
+            "over" => Some(FullscreenPolicy::Over),
+            "refuse" => Some(FullscreenPolicy::Refuse),
+            _ => {
+                let _ = log(format!("Unknown fullscreen policy: {name}"), Warn);
+                None
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ScratchpadOptions {
     options_string: String,
     pub monitor: Option<String>,
+    pub only_on_workspace: Option<String>,
+    pub only_on_monitor: Option<String>,
+    pub fullscreen: Option<FullscreenPolicy>,
     pub ephemeral: bool,
     pub persist: bool,
+    pub per_workspace: bool,
+    pub inherit_cwd: bool,
+    pub respawn: bool,
     pub special: bool,
     pub sticky: bool,
     pub shiny: bool,
@@ -80,6 +264,9 @@ impl ScratchpadOptions {
             options_string: opts.to_string(),
             ephemeral: opts.contains("ephemeral"),
             persist: opts.contains("persist"),
+            per_workspace: opts.contains("per_workspace"),
+            inherit_cwd: opts.contains("inherit_cwd"),
+            respawn: opts.contains("respawn"),
             special: opts.contains("special"),
             sticky: opts.contains("sticky"),
             shiny: opts.contains("shiny"),
@@ -91,6 +278,9 @@ impl ScratchpadOptions {
             lazy: opts.contains("lazy"),
             pin: opts.contains("pin"),
             monitor: get_arg("monitor"),
+            only_on_workspace: get_arg("only_on_workspace"),
+            only_on_monitor: get_arg("only_on_monitor"),
+            fullscreen: get_arg("fullscreen").and_then(|f| FullscreenPolicy::new(&f)),
         }
     }
 
@@ -98,6 +288,9 @@ impl ScratchpadOptions {
         match opt {
             "persist" => self.persist ^= true,
             "ephemeral" => self.ephemeral ^= true,
+            "per_workspace" => self.per_workspace ^= true,
+            "inherit_cwd" => self.inherit_cwd ^= true,
+            "respawn" => self.respawn ^= true,
             "special" => self.special ^= true,
             "summon" => self.show ^= true,
             "sticky" => self.sticky ^= true,
@@ -135,6 +328,13 @@ pub struct Scratchpad {
     pub command: String,
     pub rules: String,
     pub options: ScratchpadOptions,
+    pub when: Vec<Condition>,
+    pub hooks: Hooks,
+    pub env: Vec<(String, String)>,
+    pub cwd: Option<String>,
+    pub workspace: Option<String>,
+    pub steps: Vec<Step>,
+    pub binds: Vec<String>,
 }
 
 impl Scratchpad {
@@ -144,7 +344,99 @@ impl Scratchpad {
             command: command.into(),
             rules: rules.into(),
             options: ScratchpadOptions::new(options),
+            when: vec![],
+            hooks: Hooks::default(),
+            env: vec![],
+            cwd: None,
+            workspace: None,
+            steps: vec![],
+            binds: vec![],
+        }
+    }
+
+    pub fn is_parameterized(&self) -> bool {
+        let no_args = HashMap::new();
+        [&self.title, &self.command, &self.rules]
+            .into_iter()
+            .chain(&self.cwd)
+            .chain(self.env.iter().map(|(_, v)| v))
+            .chain(self.steps.iter().filter_map(|s| s.after.as_ref()))
+            .any(|text| fill_args(text, &no_args).is_err())
+    }
+
+    pub fn untitled_args(&self) -> Vec<String> {
+        let titled = arg_keys(&self.title);
+        let mut untitled: Vec<String> = [&self.command, &self.rules]
+            .into_iter()
+            .chain(&self.cwd)
+            .chain(self.env.iter().map(|(_, v)| v))
+            .chain(self.steps.iter().filter_map(|s| s.after.as_ref()))
+            .flat_map(|text| arg_keys(text))
+            .filter(|key| !titled.contains(key))
+            .collect();
+        untitled.dedup();
+        untitled
+    }
+
+    pub fn instantiate(
+        &self,
+        args: &HashMap<String, String>,
+    ) -> std::result::Result<Scratchpad, String> {
+        let mut instance = self.clone();
+        instance.title = fill_args(&self.title, args)?;
+        instance.command = fill_args(&self.command, args)?;
+        instance.rules = fill_args(&self.rules, args)?;
+        if let Some(cwd) = &self.cwd {
+            instance.cwd = Some(fill_args(cwd, args)?);
+        }
+        for (_, value) in instance.env.iter_mut() {
+            *value = fill_args(value, args)?;
+        }
+        for after in instance.steps.iter_mut().filter_map(|s| s.after.as_mut()) {
+            *after = fill_args(after, args)?;
         }
+        Ok(instance)
+    }
+
+    pub fn add_env(&mut self, env: &str) {
+        for var in env.lines().map(str::trim).filter(|v| !v.is_empty()) {
+            match var.split_once('=') {
+                Some((k, v)) if !k.trim().is_empty() => {
+                    self.env.push((k.trim().into(), v.trim().into()));
+                }
+                _ => {
+                    let _ = log(format!("Invalid environment variable '{var}'"), Warn);
+                }
+            }
+        }
+    }
+
+    pub fn add_conditions(&mut self, when: &str) {
+        self.when.extend(
+            when.split(',')
+                .filter(|w| !w.trim().is_empty())
+                .filter_map(Condition::parse),
+        );
+    }
+
+    pub fn redirect(&self, location: &Location) -> Option<&str> {
+        self.when
+            .iter()
+            .find(|c| location.matches(&c.target, &c.pattern))
+            .map(|c| c.name.as_str())
+    }
+
+    pub fn is_allowed(&self, location: &Location) -> bool {
+        let allowed = |target, pattern: &Option<String>| {
+            pattern.as_ref().is_none_or(|p| location.matches(target, p))
+        };
+
+        allowed("workspace", &self.options.only_on_workspace)
+            && allowed("monitor", &self.options.only_on_monitor)
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        self.options.only_on_workspace.is_some() || self.options.only_on_monitor.is_some()
     }
 
     pub fn add_rules(&mut self, rules: &str) {
@@ -158,16 +450,39 @@ impl Scratchpad {
         self.options = ScratchpadOptions::new(&format!("{} {}", self.options.as_str(), options));
     }
 
+    pub fn set_workspace(&mut self, workspace: &str) {
+        let workspace = workspace.trim();
+        let workspace = workspace.strip_prefix("special:").unwrap_or(workspace);
+        if !workspace.is_empty() {
+            self.workspace = Some(workspace.into());
+        }
+    }
+
+    pub fn hide_workspace(&self, name: &str) -> String {
+        let name = self.workspace.as_deref().unwrap_or(name);
+        if self.options.per_workspace {
+            format!("{name}-{{workspace}}")
+        } else {
+            name.into()
+        }
+    }
+
+    pub fn matches(&self, title: &str, class: &str) -> bool {
+        self.title == title || self.title == class
+    }
+
     pub fn matches_client(&self, client: &Client) -> bool {
-        self.title == client.initial_title || self.title == client.initial_class
+        self.matches(&client.initial_title, &client.initial_class)
     }
 
     fn capture_special(&self, state: &HyprlandState) -> Result<()> {
         let first_title = &state.clients_with_title[0];
-        move_to_special(first_title, &state.special_workspace);
+        send_to_special(first_title, &state.special_workspace);
+        apply_rules(self, first_title, None, true);
 
         if !self.options.hide && first_title.workspace.id == state.active_workspace.id {
             state.toggle_special()?;
+            run_client_hook(Hook::Show, &self.hooks, &state.name, first_title);
         }
         Ok(())
     }
@@ -179,19 +494,32 @@ impl Scratchpad {
 
             if should_toggle {
                 state.toggle_special()?;
+                let hook = if self.matches_client(ac) {
+                    Hook::Hide
+                } else {
+                    Hook::Show
+                };
+                self.run_special_hook(hook, state);
             }
         } else if !self.options.hide {
             state.toggle_special()?;
+            self.run_special_hook(Hook::Show, state);
         }
         Ok(())
     }
 
-    fn spawn_special(&self, state: &HyprlandState) {
-        for cmd in &prepare_commands(self, Some(false), &state.special_workspace) {
-            dispatchers().exec(cmd).log_err(file!(), line!());
+    fn run_special_hook(&self, hook: Hook, state: &HyprlandState) {
+        if let Some(cl) = state.clients_with_title.iter().find(|cl| is_on_special(cl)) {
+            run_client_hook(hook, &self.hooks, &state.name, cl);
         }
     }
 
+    fn spawn_special(&self, state: &HyprlandState) {
+        let cwd = focused_cwd(self);
+        let commands = prepare_commands(self, Some(false), &state.special_workspace, cwd);
+        spawn_tracked(&state.name, &state.special_workspace, self, &commands);
+    }
+
     fn summon_special(&self, state: &HyprlandState) -> Result<()> {
         let special_with_title: Vec<&Client> = state
             .clients_with_title
@@ -215,34 +543,58 @@ impl Scratchpad {
             None => return state.active_workspace.name.clone(),
         };
 
-        state.monitors.get(monitor).cloned().unwrap_or_else(|| {
-            let _ = log(format!("Monitor {monitor} not found"), Warn);
+        resolve_monitor(&state.monitors, monitor).unwrap_or_else(|| {
+            let unplugged = monitor.split(',').all(|m| is_unplugged(m.trim()));
+
+            if unplugged {
+                let msg = format!("Monitor {monitor} is unplugged, using the active workspace");
+                let _ = log(msg, Info);
+            } else {
+                let _ = log(format!("Monitor {monitor} not found"), Warn);
+            }
             state.active_workspace.name.clone()
         })
     }
 
     fn spawn_normal(&self, state: &HyprlandState) {
+        let cwd = focused_cwd(self);
         if let Some(ac) = &state.active_client {
             hide_special(ac);
         }
 
-        for cmd in &prepare_commands(self, None, &state.special_workspace) {
-            dispatchers().exec(cmd).log_err(file!(), line!());
-        }
+        let commands = prepare_commands(self, None, &state.special_workspace, cwd);
+        spawn_tracked(&state.name, &state.special_workspace, self, &commands);
     }
 
     fn show_normal(&self, state: &HyprlandState) -> Result<()> {
+        let workspace = self.get_workspace_name(state);
+        let monitor = state.monitor_of(&workspace);
+
         for client in state
             .clients_with_title
             .iter()
             .filter(|cl| !self.is_on_workspace(cl, state))
         {
             dispatchers().move_to_workspace_silent(
-                WorkspaceIdentifierWithSpecial::Name(&self.get_workspace_name(state)),
+                WorkspaceIdentifierWithSpecial::Name(&workspace),
                 Some(WindowIdentifier::Address(client.address.clone())),
             )?;
 
             dispatchers().focus_window(WindowIdentifier::Address(client.address.clone()))?;
+            apply_rules(self, client, monitor, false);
+            run_hook(
+                Hook::Show,
+                &self.hooks,
+                &state.name,
+                &client.address,
+                &workspace,
+                monitor,
+            );
+
+            if self.options.fullscreen == Some(FullscreenPolicy::Over) {
+                dispatchers()
+                    .alter_zorder_top(WindowIdentifier::Address(client.address.clone()))?;
+            }
 
             if self.options.pin && !client.pinned {
                 dispatchers()
@@ -275,7 +627,7 @@ impl Scratchpad {
         Ok(())
     }
 
-    fn hide_active(&self, titles: &HashMap<String, String>, state: &HyprlandState) {
+    fn hide_active(&self, config: &Config, state: &HyprlandState) {
         if self.options.cover || self.options.hide {
             return;
         }
@@ -284,8 +636,9 @@ impl Scratchpad {
             if !self.matches_client(ac)
                 && ac.workspace.id == state.active_workspace.id
                 && ac.floating
+                && auto_hide(ac, &config.cache.replace_map)
             {
-                auto_hide(ac, titles);
+                run_config_hook(Hook::Hide, config, ac);
             }
         }
     }
@@ -333,22 +686,63 @@ impl Scratchpad {
         Ok(())
     }
 
-    fn hide(clients: Vec<&Client>, state: &HyprlandState) {
-        clients
-            .into_iter()
-            .for_each(|c| move_to_special(c, &state.special_workspace));
+    fn hide(&self, clients: Vec<&Client>, state: &HyprlandState) {
+        for cl in clients {
+            move_to_special(cl, &state.special_workspace);
+            run_client_hook(Hook::Hide, &self.hooks, &state.name, cl);
+        }
+    }
+
+    fn get_fullscreen(&self, state: &HyprlandState) -> Result<Option<Client>> {
+        if !state.active_workspace.fullscreen || self.options.special || self.options.hide {
+            return Ok(None);
+        }
+
+        Ok(Clients::get()?.into_iter().find(|cl| {
+            cl.workspace.id == state.active_workspace.id
+                && cl.fullscreen != FullscreenMode::None
+                && !self.matches_client(cl)
+        }))
+    }
+
+    fn handle_fullscreen(&self, state: &HyprlandState) -> Result<bool> {
+        let (policy, fullscreen) = match (self.options.fullscreen, self.get_fullscreen(state)?) {
+            (Some(policy), Some(cl)) => (policy, cl),
+            _ => return Ok(true),
+        };
+
+        match policy {
+            FullscreenPolicy::Refuse => {
+                let msg = format!("Not showing '{}' over a fullscreen window", state.name);
+                log(msg, Info)?;
+                return Ok(false);
+            }
+            FullscreenPolicy::Unset => {
+                dispatchers()
+                    .focus_window(WindowIdentifier::Address(fullscreen.address.clone()))?;
+                dispatchers().set_fullscreen_state(FullscreenMode::None as u8)?;
+                remember_fullscreen(&state.name, fullscreen.address, fullscreen.fullscreen);
+            }
+            FullscreenPolicy::Over => (),
+        }
+        Ok(true)
     }
 
-    pub fn trigger(&self, title_map: &HashMap<String, String>, name: &str) -> Result<()> {
-        let state = HyprlandState::new(&self.title, name)?;
+    pub fn trigger(&self, config: &Config, name: &str) -> Result<()> {
+        let state = HyprlandState::new(self, name)?;
+        let mode = self.get_mode(&state);
 
-        match self.get_mode(&state) {
+        if !matches!(mode, Hide(_)) && !self.handle_fullscreen(&state)? {
+            return Ok(());
+        }
+
+        match mode {
             Refocus(client) => Self::refocus(client)?,
-            Hide(clients) => Self::hide(clients, &state),
+            Hide(clients) => self.hide(clients, &state),
             Summon => self.summon(&state)?,
         }
 
-        self.hide_active(title_map, &state);
+        self.hide_active(config, &state);
         dispatchers().bring_active_to_top()?;
         Ok(())
     }
@@ -357,13 +751,30 @@ impl Scratchpad {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ConfigCache;
     use std::thread::sleep;
     use std::time::Duration;
 
     struct TestResources {
         title: String,
         command: String,
-        title_map: HashMap<String, String>,
+        config: Config,
+    }
+
+    fn test_config(title: &str) -> Config {
+        let mut cache = ConfigCache::new(&HashMap::new());
+        cache.replace_map = HashMap::from([(title.to_string(), title.to_string())]);
+        Config {
+            daemon_options: String::new(),
+            config_file: String::new(),
+            scratchpads: HashMap::new(),
+            groups: HashMap::new(),
+            profiles: HashMap::new(),
+            names: Vec::new(),
+            instances: HashMap::new(),
+            attached: HashMap::new(),
+            cache,
+        }
     }
 
     impl TestResources {
@@ -372,7 +783,7 @@ mod tests {
             TestResources {
                 title: title.clone(),
                 command: format!("[size 30% 30%] kitty --title {title}"),
-                title_map: HashMap::from([(title.clone(), title)]),
+                config: test_config(&title),
             }
         }
 
@@ -452,14 +863,14 @@ mod tests {
         resources.assert_not_present();
 
         scratchpad
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
         resources.assert_active();
 
         scratchpad
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
@@ -467,7 +878,7 @@ mod tests {
         resources.assert_not_active();
 
         scratchpad
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
@@ -483,14 +894,14 @@ mod tests {
         resources.assert_not_present();
 
         scratchpad
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
         resources.assert_active();
 
         scratchpad
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
@@ -498,7 +909,7 @@ mod tests {
         resources.assert_not_active();
 
         scratchpad
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
@@ -517,14 +928,14 @@ mod tests {
         resources.iter().for_each(TestResources::assert_not_present);
 
         scratchpads[0]
-            .trigger(&resources[0].title_map, &resources[0].title)
+            .trigger(&resources[0].config, &resources[0].title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
         resources[0].assert_active();
 
         scratchpads[1]
-            .trigger(&resources[1].title_map, &resources[0].title)
+            .trigger(&resources[1].config, &resources[0].title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
@@ -538,21 +949,21 @@ mod tests {
         let resources = TestResources {
             title: title.clone(),
             command: "[size 30% 30%; move 0 0] kitty --title test_poly ? [size 30% 30%; move 30% 0] kitty --title test_poly".to_string(),
-            title_map: HashMap::from([(title.clone(), title)]),
+            config: test_config(&title),
         };
         let scratchpad = resources.into_scratchpad("poly");
 
         resources.assert_not_present();
 
         scratchpad
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
         resources.assert_active();
 
         scratchpad
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
@@ -567,7 +978,7 @@ mod tests {
         resources.assert_not_present();
 
         scratchpad
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
@@ -591,7 +1002,7 @@ mod tests {
         resources.assert_not_present();
         resources
             .into_scratchpad("show")
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
@@ -599,7 +1010,7 @@ mod tests {
 
         resources
             .into_scratchpad("show")
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
@@ -608,7 +1019,7 @@ mod tests {
 
         resources
             .into_scratchpad("hide")
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
@@ -617,7 +1028,7 @@ mod tests {
 
         resources
             .into_scratchpad("hide")
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(500));
 
@@ -638,7 +1049,7 @@ mod tests {
         assert_eq!(Workspace::get_active().unwrap().name, "test");
 
         scratchpad
-            .trigger(&resources.title_map, &resources.title)
+            .trigger(&resources.config, &resources.title)
             .unwrap();
         sleep(Duration::from_millis(1000));
 
@@ -650,6 +1061,79 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_resolve_monitor() {
+        let monitors = HashMap::from([
+            ("0".to_string(), "1".to_string()),
+            ("DP-1".to_string(), "1".to_string()),
+            ("1".to_string(), "2".to_string()),
+            ("HDMI-A-1".to_string(), "2".to_string()),
+            ("focused".to_string(), "2".to_string()),
+        ]);
+
+        let test_cases = [
+            ("DP-1", Some("1")),
+            ("1", Some("2")),
+            ("focused", Some("2")),
+            ("cursor", None),
+            ("DP-2,HDMI-A-1,focused", Some("2")),
+            ("DP-2,cursor,DP-1", Some("1")),
+            ("eDP-1,HDMI-A-1", Some("2")),
+            ("DP-2,eDP-1", None),
+        ];
+
+        for (spec, expected) in test_cases {
+            assert_eq!(
+                resolve_monitor(&monitors, spec).as_deref(),
+                expected,
+                "Failed for {spec}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_fullscreen_policy() {
+        let policy = |opts| ScratchpadOptions::new(opts).fullscreen;
+        assert_eq!(
+            policy("cover fullscreen over"),
+            Some(FullscreenPolicy::Over)
+        );
+        assert_eq!(
+            policy("fullscreen unset pin"),
+            Some(FullscreenPolicy::Unset)
+        );
+        assert_eq!(policy("fullscreen refuse"), Some(FullscreenPolicy::Refuse));
+        assert_eq!(policy("fullscreen sideways"), None);
+        assert_eq!(policy("pin"), None);
+    }
+
+    #[test]
+    fn test_conditions() {
+        let location = Location {
+            workspace: Workspace {
+                id: 4,
+                name: "db".into(),
+                monitor: "HDMI-A-1".into(),
+                monitor_id: Some(1),
+                windows: 0,
+                fullscreen: false,
+                last_window: hyprland::shared::Address::new(""),
+                last_window_title: String::new(),
+            },
+        };
+
+        let mut sc = Scratchpad::new("console", "console", "", "only_on_monitor ^DP-");
+        sc.add_conditions("workspace ^db$ dbconsole, invalid, monitor 1 tvconsole");
+        sc.add_conditions("workspace ^(db dbconsole");
+        assert_eq!(sc.when.len(), 2);
+        assert_eq!(sc.redirect(&location), Some("dbconsole"));
+        assert!(!sc.is_allowed(&location));
+
+        sc.add_opts("only_on_workspace 4");
+        sc.options.only_on_monitor = None;
+        assert!(sc.is_allowed(&location));
+    }
+
     #[test]
     fn test_attach() {
         use crate::daemon::initialize_daemon;
@@ -669,7 +1153,7 @@ mod tests {
             title: "test_attach".to_string(),
             command: "[float; size 30% 30%] kitty --class test_attach --title test_attach"
                 .to_string(),
-            title_map: HashMap::from([("test_attach".to_string(), "test_attach".to_string())]),
+            config: test_config("test_attach"),
         };
 
         resources.assert_not_present();
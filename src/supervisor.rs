@@ -0,0 +1,504 @@
+use crate::config::Config;
+use crate::dispatchers::dispatchers;
+use crate::logs::*;
+use crate::rules::mark_placed;
+use crate::scratchpad::{Hook, Hooks, Scratchpad, Step};
+use crate::utils::*;
+use hyprland::ctl::{notify, Color};
+use hyprland::data::{Client, Clients, Workspace};
+use hyprland::prelude::*;
+use hyprland::shared::{Address, MonitorId};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+use std::thread::{sleep, spawn};
+use std::time::{Duration, Instant};
+
+static SUPERVISOR: OnceLock<Mutex<Supervisor>> = OnceLock::new();
+
+const MAX_RESTARTS: usize = 5;
+const RESTART_WINDOW: Duration = Duration::from_secs(60);
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const SPAWN_TIMEOUT: Duration = Duration::from_secs(10);
+const SPAWN_POLL: Duration = Duration::from_millis(200);
+
+pub fn supervisor() -> MutexGuard<'static, Supervisor> {
+    SUPERVISOR
+        .get_or_init(|| Mutex::new(Supervisor::new()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+struct PendingSpawn {
+    name: String,
+    command: String,
+    since: Instant,
+    step: usize,
+    steps: usize,
+}
+
+#[derive(Default)]
+pub struct Supervisor {
+    pending: HashMap<String, PendingSpawn>,
+    windows: HashMap<Address, String>,
+    expected: HashSet<Address>,
+    restarts: HashMap<String, Vec<Instant>>,
+    total_restarts: HashMap<String, usize>,
+    given_up: HashSet<String>,
+}
+
+impl Supervisor {
+    fn new() -> Supervisor {
+        Supervisor::default()
+    }
+
+    pub fn sync(&mut self, config: &Config) -> hyprland::Result<()> {
+        self.windows.clear();
+        for cl in Clients::get()? {
+            if let Some(name) = config.get_name(&cl.initial_title, &cl.initial_class) {
+                self.windows.insert(cl.address, name);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn track(&mut self, address: Address, name: &str) {
+        self.windows.insert(address, name.into());
+    }
+
+    pub fn name_of(&self, address: &Address) -> Option<String> {
+        self.windows.get(address).cloned()
+    }
+
+    pub fn expect_close(&mut self, address: &Address) {
+        self.expected.insert(address.clone());
+    }
+
+    pub fn untrack(&mut self, address: &Address) -> Option<(String, bool)> {
+        let name = self.windows.remove(address)?;
+        Some((name, self.expected.remove(address)))
+    }
+
+    fn next_backoff(&mut self, name: &str, now: Instant) -> Option<Duration> {
+        if self.given_up.contains(name) {
+            return None;
+        }
+
+        let restarts = self.restarts.entry(name.into()).or_default();
+        restarts.retain(|t| now.duration_since(*t) < RESTART_WINDOW);
+
+        if restarts.len() >= MAX_RESTARTS {
+            self.given_up.insert(name.into());
+            return None;
+        }
+
+        let backoff = BASE_BACKOFF * 2u32.pow(restarts.len() as u32);
+        restarts.push(now);
+        *self.total_restarts.entry(name.into()).or_default() += 1;
+        Some(backoff)
+    }
+
+    fn begin_spawn(&mut self, key: &str, sc: &Scratchpad, name: &str, now: Instant) -> bool {
+        if let Some(pending) = self.pending.get(key) {
+            if now.duration_since(pending.since) < SPAWN_TIMEOUT {
+                return false;
+            }
+        }
+
+        let pending = PendingSpawn {
+            name: name.into(),
+            command: sc.command.clone(),
+            since: now,
+            step: 0,
+            steps: sc.command.split('?').count(),
+        };
+        self.pending.insert(key.into(), pending);
+        true
+    }
+
+    fn advance_spawn(&mut self, key: &str, step: usize, now: Instant) {
+        if let Some(pending) = self.pending.get_mut(key) {
+            pending.step = step;
+            pending.since = now;
+        }
+    }
+
+    fn end_spawn(&mut self, key: &str) -> Option<PendingSpawn> {
+        self.pending.remove(key)
+    }
+
+    pub fn reset(&mut self, name: &str) {
+        self.restarts.remove(name);
+        self.given_up.remove(name);
+    }
+
+    pub fn status(&self, config: &Config) -> String {
+        let mut status = String::from("Pending spawns:\n");
+        if self.pending.is_empty() {
+            status.push_str("  none\n");
+        }
+
+        for (key, pending) in &self.pending {
+            let cmd = &pending.command;
+            let elapsed = pending.since.elapsed().as_secs();
+            let step = match pending.steps {
+                1 => String::new(),
+                n => format!(", step {}/{n}", pending.step),
+            };
+            status.push_str(&format!("  {key}: '{cmd}' ({elapsed}s{step})\n"));
+        }
+
+        status.push_str("Respawn:\n");
+        let respawning = config
+            .names
+            .iter()
+            .filter(|n| config.scratchpads[*n].options.respawn)
+            .collect::<Vec<_>>();
+
+        if respawning.is_empty() {
+            status.push_str("  no scratchpads with 'respawn'\n");
+        }
+
+        for name in respawning {
+            let count = self.total_restarts.get(name).unwrap_or(&0);
+            let stopped = if self.given_up.contains(name) {
+                ", stopped after crash loop"
+            } else {
+                ""
+            };
+            status.push_str(&format!("  {name}: {count} restarts{stopped}\n"));
+        }
+        status
+    }
+}
+
+fn describe_clients(clients: &[Client]) -> String {
+    clients
+        .iter()
+        .map(|cl| format!("title '{}' class '{}'", cl.initial_title, cl.initial_class))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn report_spawn_failure(pending: &PendingSpawn, title: &str, new_clients: &[Client]) {
+    let mut msg = format!(
+        "Scratchpad '{}' did not open a window with title or class '{title}' within {}s, command: '{}'",
+        pending.name,
+        SPAWN_TIMEOUT.as_secs(),
+        pending.command
+    );
+
+    if !new_clients.is_empty() {
+        msg.push_str(&format!(". New windows: {}", describe_clients(new_clients)));
+    }
+
+    let _ = log(msg.clone(), Warn);
+    notify::call(
+        notify::Icon::Error,
+        Duration::from_secs(5),
+        Color::new(255, 85, 85, 255),
+        msg,
+    )
+    .log_err(file!(), line!());
+}
+
+fn watch_spawn(key: String, sc: Scratchpad, known: HashSet<Address>) {
+    let start = Instant::now();
+    let mut new_clients = vec![];
+
+    while start.elapsed() < SPAWN_TIMEOUT {
+        sleep(SPAWN_POLL);
+        let clients = match Clients::get() {
+            Ok(clients) => clients,
+            Err(_) => continue,
+        };
+
+        let spawned = clients
+            .iter()
+            .find(|cl| !known.contains(&cl.address) && sc.matches_client(cl));
+
+        if let Some(cl) = spawned {
+            let pending = {
+                let mut supervisor = supervisor();
+                let pending = supervisor.end_spawn(&key);
+                if let Some(pending) = &pending {
+                    supervisor.track(cl.address.clone(), &pending.name);
+                }
+                pending
+            };
+
+            mark_placed(&cl.address);
+
+            if let Some(pending) = pending {
+                run_client_hook(Hook::Spawn, &sc.hooks, &pending.name, cl);
+            }
+            return;
+        }
+
+        new_clients = clients
+            .into_iter()
+            .filter(|cl| !known.contains(&cl.address))
+            .collect();
+    }
+
+    let pending = supervisor().end_spawn(&key);
+    if let Some(pending) = pending {
+        report_spawn_failure(&pending, &sc.title, &new_clients);
+    }
+}
+
+fn is_ready(target: &str) -> bool {
+    if target.starts_with('/') || target.starts_with('~') {
+        return Path::new(&expand_home(target)).exists();
+    }
+
+    Clients::get().is_ok_and(|clients| clients.iter().any(|cl| is_known(&[target.into()], cl)))
+}
+
+fn wait_for(target: &str) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < SPAWN_TIMEOUT {
+        if is_ready(target) {
+            return true;
+        }
+        sleep(SPAWN_POLL);
+    }
+    false
+}
+
+fn run_steps(key: String, sc: Scratchpad, commands: Vec<String>, known: HashSet<Address>) {
+    for (i, cmd) in commands.iter().enumerate() {
+        let step = sc.steps.get(i).cloned().unwrap_or_default();
+        supervisor().advance_spawn(&key, i + 1, Instant::now());
+
+        if let Some(target) = &step.after {
+            if !wait_for(target) {
+                let msg = format!(
+                    "Scratchpad '{key}' stopped at step {}: '{target}' did not appear within {}s",
+                    i + 1,
+                    SPAWN_TIMEOUT.as_secs()
+                );
+                let _ = log(msg, Warn);
+                supervisor().end_spawn(&key);
+                return;
+            }
+        }
+
+        sleep(step.delay);
+        let msg = format!("Scratchpad '{key}' step {}: '{cmd}'", i + 1);
+        let _ = log(msg, Debug);
+        dispatchers().exec(cmd).log_err(file!(), line!());
+    }
+    watch_spawn(key, sc, known);
+}
+
+pub fn run_hook(
+    hook: Hook,
+    hooks: &Hooks,
+    name: &str,
+    address: &Address,
+    workspace: &str,
+    monitor: Option<MonitorId>,
+) {
+    let cmd = hooks.get(hook);
+    if cmd.is_empty() {
+        return;
+    }
+
+    let monitor = monitor.map(|m| m.to_string()).unwrap_or_default();
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .env("HYPRSCRATCH_EVENT", hook.as_str())
+        .env("HYPRSCRATCH_NAME", name)
+        .env("HYPRSCRATCH_ADDRESS", address.to_string())
+        .env("HYPRSCRATCH_WORKSPACE", workspace)
+        .env("HYPRSCRATCH_MONITOR", monitor)
+        .spawn();
+
+    match child {
+        Ok(mut child) => {
+            spawn(move || child.wait());
+        }
+        Err(e) => {
+            let msg = format!("Could not run 'on_{}' of '{name}': {e}", hook.as_str());
+            let _ = log(msg, Warn);
+        }
+    }
+}
+
+pub fn run_client_hook(hook: Hook, hooks: &Hooks, name: &str, cl: &Client) {
+    run_hook(
+        hook,
+        hooks,
+        name,
+        &cl.address,
+        &cl.workspace.name,
+        cl.monitor,
+    );
+}
+
+pub fn run_config_hook(hook: Hook, config: &Config, cl: &Client) {
+    let name = match config.get_name(&cl.initial_title, &cl.initial_class) {
+        Some(name) => name,
+        None => return,
+    };
+
+    if let Some(sc) = config.get(&name) {
+        run_client_hook(hook, &sc.hooks, &name, cl);
+    }
+}
+
+fn spawn_key(name: &str, sc: &Scratchpad, workspace: &str) -> String {
+    if sc.options.per_workspace {
+        format!("{name}@{workspace}")
+    } else {
+        name.into()
+    }
+}
+
+pub fn spawn_tracked(name: &str, workspace: &str, sc: &Scratchpad, commands: &[String]) {
+    let key = spawn_key(name, sc, workspace);
+    let started = supervisor().begin_spawn(&key, sc, name, Instant::now());
+    if !started {
+        let _ = log(format!("Scratchpad '{key}' is still spawning"), Info);
+        return;
+    }
+
+    let known = Clients::get()
+        .map(|clients| clients.into_iter().map(|cl| cl.address).collect())
+        .unwrap_or_default();
+
+    let sc = sc.clone();
+    if sc.steps.iter().any(|s| *s != Step::default()) {
+        let commands = commands.to_vec();
+        spawn(move || run_steps(key, sc, commands, known));
+        return;
+    }
+
+    for cmd in commands {
+        dispatchers().exec(cmd).log_err(file!(), line!());
+    }
+    spawn(move || watch_spawn(key, sc, known));
+}
+
+pub fn respawn(name: String, sc: Scratchpad) {
+    let backoff = match supervisor().next_backoff(&name, Instant::now()) {
+        Some(b) => b,
+        None => {
+            let msg = format!("Scratchpad '{name}' keeps closing, not respawning it again");
+            let _ = log(msg, Warn);
+            return;
+        }
+    };
+
+    spawn(move || {
+        sleep(backoff);
+        if let Ok(clients) = Clients::get() {
+            if clients.iter().any(|cl| sc.matches_client(cl)) {
+                return;
+            }
+        }
+
+        let workspace = match Workspace::get_active() {
+            Ok(ws) => ws.id,
+            Err(e) => {
+                let _ = log(format!("Could not respawn scratchpad '{name}': {e}"), Warn);
+                return;
+            }
+        };
+
+        let _ = log(format!("Respawning scratchpad '{name}'"), Info);
+        spawn_hidden(&name, &sc, workspace);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let mut supervisor = Supervisor::new();
+        let now = Instant::now();
+
+        let backoffs: Vec<_> = (0..MAX_RESTARTS)
+            .map(|_| supervisor.next_backoff("test", now))
+            .collect();
+
+        assert_eq!(backoffs[0], Some(BASE_BACKOFF));
+        assert_eq!(backoffs[1], Some(BASE_BACKOFF * 2));
+        assert_eq!(backoffs[4], Some(BASE_BACKOFF * 16));
+        assert_eq!(supervisor.next_backoff("test", now), None);
+        assert_eq!(supervisor.total_restarts["test"], MAX_RESTARTS);
+
+        supervisor.reset("test");
+        assert_eq!(supervisor.next_backoff("test", now), Some(BASE_BACKOFF));
+    }
+
+    #[test]
+    fn test_restart_window() {
+        let mut supervisor = Supervisor::new();
+        let start = Instant::now();
+
+        for _ in 0..MAX_RESTARTS - 1 {
+            supervisor.next_backoff("test", start);
+        }
+
+        let later = start + RESTART_WINDOW;
+        assert_eq!(supervisor.next_backoff("test", later), Some(BASE_BACKOFF));
+    }
+
+    #[test]
+    fn test_pending_spawn() {
+        let mut supervisor = Supervisor::new();
+        let sc = Scratchpad::new("test", "test", "", "");
+        let now = Instant::now();
+
+        assert!(supervisor.begin_spawn("test", &sc, "test", now));
+        assert!(!supervisor.begin_spawn("test", &sc, "test", now));
+        assert!(supervisor.begin_spawn("test", &sc, "test", now + SPAWN_TIMEOUT));
+
+        assert!(supervisor.end_spawn("test").is_some());
+        assert!(supervisor.end_spawn("test").is_none());
+        assert!(supervisor.begin_spawn("test", &sc, "test", now));
+
+        supervisor.advance_spawn("test", 2, now + SPAWN_TIMEOUT);
+        assert!(!supervisor.begin_spawn("test", &sc, "test", now + SPAWN_TIMEOUT));
+        assert_eq!(supervisor.pending["test"].step, 2);
+    }
+
+    #[test]
+    fn test_spawn_key() {
+        let mut supervisor = Supervisor::new();
+        let mut sc = Scratchpad::new("term", "kitty", "", "per_workspace");
+        let now = Instant::now();
+
+        let (first, second) = (
+            spawn_key("term", &sc, "term-1"),
+            spawn_key("term", &sc, "term-2"),
+        );
+        assert_eq!(first, "term@term-1");
+        assert!(supervisor.begin_spawn(&first, &sc, "term", now));
+        assert!(supervisor.begin_spawn(&second, &sc, "term", now));
+        assert!(!supervisor.begin_spawn(&first, &sc, "term", now));
+
+        sc.options.per_workspace = false;
+        assert_eq!(spawn_key("term", &sc, "term"), "term");
+    }
+
+    #[test]
+    fn test_expected_close() {
+        let mut supervisor = Supervisor::new();
+        let (a1, a2) = (Address::new("1"), Address::new("2"));
+
+        supervisor.track(a1.clone(), "one");
+        supervisor.track(a2.clone(), "two");
+        supervisor.expect_close(&a1);
+
+        assert_eq!(supervisor.untrack(&a1), Some(("one".into(), true)));
+        assert_eq!(supervisor.untrack(&a2), Some(("two".into(), false)));
+        assert_eq!(supervisor.untrack(&a2), None);
+    }
+}
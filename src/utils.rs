@@ -1,15 +1,23 @@
 use crate::config::Config;
-use crate::scratchpad::Scratchpad;
+use crate::dispatchers::dispatchers;
+use crate::scratchpad::{Hook, Scratchpad};
+use crate::supervisor::{run_config_hook, spawn_tracked, supervisor};
 use crate::DEFAULT_SOCKET;
 use crate::{logs::*, KNOWN_CLI_COMMANDS};
-use hyprland::data::{Client, Clients};
-use crate::dispatchers::dispatchers;
+use hyprland::data::{Client, Clients, FullscreenMode, Monitor, Workspace};
 use hyprland::dispatch::{WindowIdentifier, WorkspaceIdentifierWithSpecial};
 use hyprland::prelude::*;
+use hyprland::shared::{Address, WorkspaceId};
 use hyprland::Result;
+use regex::Regex;
 use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::os::unix::net::UnixStream;
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::Duration;
+
+static UNSET_FULLSCREEN: OnceLock<Mutex<HashMap<String, (Address, FullscreenMode)>>> =
+    OnceLock::new();
 
 pub fn warn_deprecated(feature: &str) -> Result<()> {
     log(format!("The '{feature}' feature is deprecated."), Warn)?;
@@ -105,6 +113,15 @@ pub fn send_request(socket: Option<&str>, req: &str, msg: &str) -> Result<()> {
 }
 
 pub fn move_to_special(cl: &Client, workspace: &str) {
+    send_to_special(cl, workspace);
+
+    let name = supervisor().name_of(&cl.address);
+    if let Some(name) = name {
+        restore_fullscreen(&name);
+    }
+}
+
+pub fn send_to_special(cl: &Client, workspace: &str) {
     if cl.pinned {
         dispatchers()
             .toggle_pin_window(WindowIdentifier::Address(cl.address.clone()))
@@ -121,6 +138,33 @@ pub fn move_to_special(cl: &Client, workspace: &str) {
         });
 }
 
+fn unset_fullscreen() -> MutexGuard<'static, HashMap<String, (Address, FullscreenMode)>> {
+    UNSET_FULLSCREEN
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+pub fn remember_fullscreen(name: &str, address: Address, mode: FullscreenMode) {
+    unset_fullscreen().insert(name.into(), (address, mode));
+}
+
+fn take_fullscreen(name: &str) -> Option<(Address, FullscreenMode)> {
+    unset_fullscreen().remove(name)
+}
+
+pub fn restore_fullscreen(name: &str) {
+    let (address, mode) = match take_fullscreen(name) {
+        Some(restore) => restore,
+        None => return,
+    };
+
+    dispatchers()
+        .focus_window(WindowIdentifier::Address(address))
+        .and_then(|_| dispatchers().set_fullscreen_state(mode as u8))
+        .log_err(file!(), line!());
+}
+
 pub fn is_known(titles: &[String], cl: &Client) -> bool {
     titles.contains(&cl.initial_title) || titles.contains(&cl.initial_class)
 }
@@ -129,11 +173,54 @@ pub fn is_known_map(map: &HashMap<String, String>, cl: &Client) -> bool {
     map.contains_key(&cl.initial_title) || map.contains_key(&cl.initial_class)
 }
 
-pub fn auto_hide(cl: &Client, title_map: &HashMap<String, String>) {
+pub fn expand_workspace(workspace: &str, id: WorkspaceId) -> String {
+    workspace.replace("{workspace}", &id.to_string())
+}
+
+pub fn auto_hide(cl: &Client, title_map: &HashMap<String, String>) -> bool {
+    let expand = |ws: &String| expand_workspace(ws, cl.workspace.id);
     if title_map.contains_key(&cl.initial_title) {
-        move_to_special(cl, &title_map[&cl.initial_title]);
+        move_to_special(cl, &expand(&title_map[&cl.initial_title]));
     } else if title_map.contains_key(&cl.initial_class) {
-        move_to_special(cl, &title_map[&cl.initial_class]);
+        move_to_special(cl, &expand(&title_map[&cl.initial_class]));
+    } else {
+        return false;
+    }
+    true
+}
+
+pub fn restore_window(cl: &Client, workspace: WorkspaceId, (x, y): (i16, i16), pinned: bool) {
+    let win = || WindowIdentifier::Address(cl.address.clone());
+    dispatchers()
+        .move_to_workspace_silent(WorkspaceIdentifierWithSpecial::Id(workspace), Some(win()))
+        .log_err(file!(), line!());
+    dispatchers()
+        .move_window_pixel((x.into(), y.into()), win())
+        .log_err(file!(), line!());
+
+    if pinned && !cl.pinned {
+        dispatchers()
+            .toggle_pin_window(win())
+            .log_err(file!(), line!());
+    }
+}
+
+pub fn release_window(cl: &Client, workspace: WorkspaceId) {
+    let win = || WindowIdentifier::Address(cl.address.clone());
+    if cl.pinned {
+        dispatchers()
+            .toggle_pin_window(win())
+            .log_err(file!(), line!());
+    }
+
+    if is_on_special(cl) {
+        dispatchers()
+            .move_to_workspace_silent(WorkspaceIdentifierWithSpecial::Id(workspace), Some(win()))
+            .log_err(file!(), line!());
+    }
+
+    if cl.floating {
+        dispatchers().set_tiled(win()).log_err(file!(), line!());
     }
 }
 
@@ -149,12 +236,94 @@ pub fn is_on_special(cl: &Client) -> bool {
     cl.workspace.name.contains("special")
 }
 
-pub fn move_floating(titles: &HashMap<String, String>) -> Result<()> {
-    Clients::get()?
-        .iter()
+pub fn move_floating(titles: &HashMap<String, String>) -> Result<Vec<Client>> {
+    Ok(Clients::get()?
+        .into_iter()
         .filter(|cl| cl.floating && !is_on_special(cl))
-        .for_each(|cl| auto_hide(cl, titles));
-    Ok(())
+        .filter(|cl| auto_hide(cl, titles))
+        .collect())
+}
+
+pub fn hide_floating(config: &Config, titles: &HashMap<String, String>) -> Result<Vec<Client>> {
+    let clients = move_floating(titles)?;
+    for cl in &clients {
+        run_config_hook(Hook::Hide, config, cl);
+    }
+    Ok(clients)
+}
+
+pub fn monitor_geometry(monitor: &Monitor) -> (i32, i32, i32, i32) {
+    let (width, height) = match monitor.transform as u8 % 2 {
+        1 => (monitor.height, monitor.width),
+        _ => (monitor.width, monitor.height),
+    };
+
+    let scale = if monitor.scale > 0.0 {
+        monitor.scale
+    } else {
+        1.0
+    };
+    (
+        monitor.x,
+        monitor.y,
+        (f32::from(width) / scale) as i32,
+        (f32::from(height) / scale) as i32,
+    )
+}
+
+pub fn contains_point((x, y, w, h): (i32, i32, i32, i32), (px, py): (i64, i64)) -> bool {
+    let (x, y, w, h) = (i64::from(x), i64::from(y), i64::from(w), i64::from(h));
+    px >= x && px < x + w && py >= y && py < y + h
+}
+
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let (number, scale) = match (text.strip_suffix("ms"), text.strip_suffix('s')) {
+        (Some(ms), _) => (ms, 0.001),
+        (None, Some(s)) => (s, 1.0),
+        (None, None) => (text, 0.001),
+    };
+
+    let number = number.trim().parse::<f64>().ok()?;
+    (number.is_finite() && number >= 0.0).then(|| Duration::from_secs_f64(number * scale))
+}
+
+pub fn clamp_position(
+    (x, y, w, h): (i32, i32, i32, i32),
+    (px, py): (i32, i32),
+    (width, height): (i32, i32),
+) -> (i32, i32) {
+    (px.min(x + w - width).max(x), py.min(y + h - height).max(y))
+}
+
+pub fn regex_match(pattern: &str, text: &str) -> bool {
+    Regex::new(pattern).is_ok_and(|re| re.is_match(text))
+}
+
+fn is_location_id(pattern: &str) -> bool {
+    let digits = pattern.trim_start_matches('-');
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+pub fn check_pattern(pattern: &str) -> bool {
+    if is_location_id(pattern) {
+        return true;
+    }
+
+    match Regex::new(pattern) {
+        Ok(_) => true,
+        Err(e) => {
+            let _ = log(format!("Invalid pattern '{pattern}': {e}"), Warn);
+            false
+        }
+    }
+}
+
+pub fn matches_location(pattern: &str, id: &str, name: &str) -> bool {
+    if is_location_id(pattern) {
+        return pattern == id;
+    }
+    regex_match(pattern, name)
 }
 
 fn prepend(command: &str, rules: &str) -> String {
@@ -181,7 +350,178 @@ pub fn prepend_rules(command: &str, rules: &str) -> Vec<String> {
     command.split('?').map(|c| prepend(c, rules)).collect()
 }
 
-pub fn prepare_commands(sc: &Scratchpad, on_special: Option<bool>, workspace: &str) -> Vec<String> {
+// Only the first child is followed, so a terminal running several shells
+// (tabs, splits) resolves to the directory of the one it started first.
+fn child_pid(pid: i32) -> Option<i32> {
+    std::fs::read_to_string(format!("/proc/{pid}/task/{pid}/children"))
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+pub fn get_cwd(pid: i32) -> Option<String> {
+    let pid = child_pid(pid).unwrap_or(pid);
+    let cwd = std::fs::read_link(format!("/proc/{pid}/cwd")).ok()?;
+    Some(cwd.to_string_lossy().into_owned())
+}
+
+pub fn get_cmdline(pid: i32) -> Option<String> {
+    let cmdline = std::fs::read_to_string(format!("/proc/{pid}/cmdline")).ok()?;
+    if cmdline.is_empty() {
+        return None;
+    }
+
+    let quote_if_needed = |arg: &str| -> String {
+        if arg.is_empty() || arg.contains([' ', '\'', '"', '?']) {
+            return quote(arg);
+        }
+        arg.into()
+    };
+    let args: Vec<String> = cmdline
+        .trim_end_matches('\0')
+        .split('\0')
+        .map(quote_if_needed)
+        .collect();
+
+    Some(args.join(" "))
+}
+
+fn active_cwd() -> Option<String> {
+    let client = Client::get_active().ok()??;
+    get_cwd(client.pid).or_else(|| std::env::var("HOME").ok())
+}
+
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => home + rest,
+        _ => path.into(),
+    }
+}
+
+pub fn quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+fn with_prefix(command: &str, prefix: &str) -> String {
+    if prefix.is_empty() {
+        return command.into();
+    }
+
+    let split = match command.trim_start().strip_prefix('[') {
+        Some(rest) => rest
+            .find(']')
+            .map_or(0, |i| command.len() - rest.len() + i + 1),
+        None => 0,
+    };
+    let (rules, command) = command.split_at(split);
+    format!("{rules} {prefix} {}", command.trim_start())
+        .trim_start()
+        .into()
+}
+
+pub fn launch_prefix(cwd: Option<&str>, env: &[(String, String)]) -> String {
+    let vars = env.iter().map(|(k, v)| quote(&format!("{k}={v}")));
+    let env = match vars.collect::<Vec<_>>() {
+        vars if vars.is_empty() => String::new(),
+        vars => format!("env {}", vars.join(" ")),
+    };
+
+    match cwd {
+        Some(cwd) => format!("cd {} && exec {env}", quote(cwd)).trim_end().into(),
+        None => env,
+    }
+}
+
+pub fn expand_command(command: &str, cwd: Option<&str>, prefix: &str) -> String {
+    command
+        .split('?')
+        .map(|c| match cwd {
+            Some(cwd) => with_prefix(&c.replace("{cwd}", &quote(cwd)), prefix),
+            None => with_prefix(c, prefix),
+        })
+        .collect::<Vec<_>>()
+        .join("?")
+}
+
+fn arg_key(placeholder: &str) -> Option<&str> {
+    match placeholder.strip_prefix("arg:") {
+        Some(key) => Some(key),
+        None if !placeholder.is_empty() && placeholder.chars().all(|c| c.is_ascii_digit()) => {
+            Some(placeholder)
+        }
+        None => None,
+    }
+}
+
+pub fn arg_keys(text: &str) -> Vec<String> {
+    let mut keys = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let tail = &rest[start..];
+        let Some(end) = tail.find('}') else {
+            break;
+        };
+
+        if let Some(key) = arg_key(&tail[1..end]) {
+            keys.push(key.to_string());
+        }
+        rest = &tail[end + 1..];
+    }
+    keys
+}
+
+pub fn fill_args(
+    text: &str,
+    args: &HashMap<String, String>,
+) -> std::result::Result<String, String> {
+    let mut filled = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let tail = &rest[start..];
+        let Some(end) = tail.find('}') else {
+            break;
+        };
+
+        filled.push_str(&rest[..start]);
+        match arg_key(&tail[1..end]) {
+            Some(key) => filled.push_str(args.get(key).ok_or(key)?),
+            None => filled.push_str(&tail[..=end]),
+        }
+        rest = &tail[end + 1..];
+    }
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+pub fn instance_key(name: &str, args: &HashMap<String, String>) -> String {
+    let mut pairs: Vec<_> = args.iter().map(|(k, v)| format!("{k}={v}")).collect();
+    pairs.sort();
+    format!("{name}:{}", pairs.join(","))
+}
+
+pub fn instance_workspace(key: &str) -> String {
+    key.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+pub fn focused_cwd(sc: &Scratchpad) -> Option<String> {
+    (sc.options.inherit_cwd || sc.command.contains("{cwd}"))
+        .then(active_cwd)
+        .flatten()
+}
+
+pub fn prepare_commands(
+    sc: &Scratchpad,
+    on_special: Option<bool>,
+    workspace: &str,
+    active: Option<String>,
+) -> Vec<String> {
     let mut rules = sc.rules.clone() + "; tag +scratchpad;";
     if let Some(is_silent) = on_special {
         let silent = if is_silent { "silent" } else { "" };
@@ -196,21 +536,33 @@ pub fn prepare_commands(sc: &Scratchpad, on_special: Option<bool>, workspace: &s
         rules += "float;";
     }
 
-    prepend_rules(&sc.command, &rules)
+    let inherit = sc.options.inherit_cwd;
+    let configured = sc.cwd.as_deref().map(expand_home);
+    let cwd = if inherit { active.clone() } else { None }.or(configured.clone());
+    let placeholder = active.or(configured).or_else(|| std::env::var("HOME").ok());
+
+    let prefix = launch_prefix(cwd.as_deref(), &sc.env);
+    let command = expand_command(&sc.command, placeholder.as_deref(), &prefix);
+    prepend_rules(&command, &rules)
 }
 
-pub fn autospawn(config: &mut Config) -> Result<()> {
-    let spawn = |(n, sc): (&String, &Scratchpad)| {
-        prepare_commands(sc, Some(true), n)
-            .iter()
-            .for_each(|cmd| dispatchers().exec(cmd).log_err(file!(), line!()));
-    };
+pub fn spawn_hidden(name: &str, sc: &Scratchpad, workspace_id: WorkspaceId) {
+    let workspace = expand_workspace(&sc.hide_workspace(name), workspace_id);
+    let commands = prepare_commands(sc, Some(true), &workspace, None);
+    spawn_tracked(name, &workspace, sc, &commands);
+}
+
+pub fn autospawn(config: &mut Config, members: Option<&[String]>) -> Result<()> {
+    let active = Workspace::get_active()?.id;
+    let spawn = |(n, sc): (&String, &Scratchpad)| spawn_hidden(n, sc, active);
 
     let clients = Clients::get()?;
     config
         .scratchpads
         .iter()
-        .filter(|(_, sc)| !sc.options.lazy && !clients.iter().any(|cl| sc.matches_client(cl)))
+        .filter(|(_, sc)| !sc.options.lazy && !sc.is_parameterized())
+        .filter(|(n, _)| members.is_none_or(|m| m.contains(n)))
+        .filter(|(_, sc)| !clients.iter().any(|cl| sc.matches_client(cl)))
         .for_each(spawn);
 
     Ok(())
@@ -241,7 +593,9 @@ mod tests {
                 .zip(self.spawned)
                 .filter(|(_, spawned)| *spawned == 1)
                 .for_each(|(title, _)| {
-                    dispatchers().close_window(WindowIdentifier::Title(&title)).unwrap();
+                    dispatchers()
+                        .close_window(WindowIdentifier::Title(&title))
+                        .unwrap();
                 });
             sleep(Duration::from_millis(500));
         }
@@ -273,19 +627,22 @@ mod tests {
         resources
             .titles
             .clone()
-            .map(|title| assert!(!clients.clone().any(|x| x.initial_title == title)));
+            .into_iter()
+            .for_each(|title| assert!(!clients.clone().any(|x| x.initial_title == title)));
 
         resources
             .commands
             .clone()
-            .map(|command| dispatchers().exec(&command).unwrap());
+            .into_iter()
+            .for_each(|command| dispatchers().exec(&command).unwrap());
         sleep(Duration::from_millis(1000));
 
         clients = Clients::get().unwrap().into_iter();
         resources
             .titles
             .clone()
-            .map(|title| assert!(clients.clone().any(|x| x.initial_title == title)));
+            .into_iter()
+            .for_each(|title| assert!(clients.clone().any(|x| x.initial_title == title)));
 
         let titles = HashMap::from([(
             "test_scratchpad_move".to_string(),
@@ -338,7 +695,8 @@ mod tests {
         resources
             .titles
             .clone()
-            .map(|title| assert!(!clients.clone().any(|x| x.initial_title == title)));
+            .into_iter()
+            .for_each(|title| assert!(!clients.clone().any(|x| x.initial_title == title)));
 
         let options = vec![
             String::new(),
@@ -359,11 +717,14 @@ mod tests {
             config_file: String::new(),
             scratchpads,
             groups: HashMap::new(),
+            profiles: HashMap::new(),
             names: Vec::new(),
+            instances: HashMap::new(),
+            attached: HashMap::new(),
             cache: ConfigCache::new(&HashMap::new()),
         };
 
-        autospawn(&mut config).unwrap();
+        autospawn(&mut config, None).unwrap();
         sleep(Duration::from_millis(1000));
 
         clients = Clients::get().unwrap().into_iter();
@@ -444,4 +805,208 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_fullscreen_stash() {
+        let (window, mode) = (Address::new("1"), FullscreenMode::Fullscreen);
+        remember_fullscreen("test_stash", window.clone(), mode);
+
+        assert_eq!(take_fullscreen("test_other"), None);
+        assert_eq!(take_fullscreen("test_stash"), Some((window, mode)));
+        assert_eq!(take_fullscreen("test_stash"), None);
+    }
+
+    #[test]
+    fn test_get_cwd() {
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "(cd /tmp && exec sleep 5) & wait"])
+            .current_dir("/")
+            .spawn()
+            .unwrap();
+        let pid = child.id() as i32;
+
+        let mut cwd = None;
+        for _ in 0..50 {
+            cwd = get_cwd(pid);
+            if cwd.as_deref() == Some("/tmp") {
+                break;
+            }
+            sleep(Duration::from_millis(20));
+        }
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(cwd.as_deref(), Some("/tmp"));
+        assert_eq!(get_cwd(i32::MAX), None);
+    }
+
+    #[test]
+    fn test_expand_command() {
+        assert_eq!(
+            expand_command("kitty --directory {cwd}", Some("/home/user"), ""),
+            "kitty --directory '/home/user'"
+        );
+        assert_eq!(expand_command("kitty {cwd}", None, ""), "kitty {cwd}");
+
+        let prefix = launch_prefix(Some("/it's"), &[]);
+        assert_eq!(
+            expand_command("[float] kitty?foot", None, &prefix),
+            "[float] cd '/it'\\''s' && exec kitty?cd '/it'\\''s' && exec foot"
+        );
+
+        let vars = [
+            ("EDITOR".into(), "nvim".into()),
+            ("GREETING".into(), "a b".into()),
+        ];
+        assert_eq!(
+            launch_prefix(None, &vars),
+            "env 'EDITOR=nvim' 'GREETING=a b'"
+        );
+        assert_eq!(
+            launch_prefix(Some("/tmp"), &vars[..1]),
+            "cd '/tmp' && exec env 'EDITOR=nvim'"
+        );
+        assert_eq!(launch_prefix(None, &[]), "");
+    }
+
+    #[test]
+    fn test_prepare_commands() {
+        let mut sc = Scratchpad::new("term", "kitty --directory {cwd}", "", "inherit_cwd");
+        sc.cwd = Some("/srv".into());
+
+        let active = Some("/tmp".to_string());
+        let commands = prepare_commands(&sc, None, "term", active);
+        assert_eq!(
+            commands,
+            ["[; tag +scratchpad;float;] cd '/tmp' && exec kitty --directory '/tmp'"]
+        );
+
+        let commands = prepare_commands(&sc, Some(true), "term", None);
+        assert_eq!(
+            commands,
+            ["[; tag +scratchpad;workspace special:term silent;float;] cd '/srv' && exec kitty --directory '/srv'"]
+        );
+    }
+
+    #[test]
+    fn test_get_cmdline() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        let mut cmdline = None;
+        for _ in 0..50 {
+            cmdline = get_cmdline(child.id() as i32);
+            if cmdline.as_deref() == Some("sleep 5") {
+                break;
+            }
+            sleep(Duration::from_millis(20));
+        }
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(cmdline.as_deref(), Some("sleep 5"));
+        assert_eq!(get_cmdline(i32::MAX), None);
+    }
+
+    #[test]
+    fn test_fill_args() {
+        let args = HashMap::from([
+            ("file".to_string(), "~/todo.md".to_string()),
+            ("1".to_string(), "notes".to_string()),
+        ]);
+
+        assert_eq!(
+            fill_args("kitty --title {1} nvim {arg:file}", &args),
+            Ok("kitty --title notes nvim ~/todo.md".into())
+        );
+        assert_eq!(
+            fill_args("kitty {cwd} {workspace} {", &args),
+            Ok("kitty {cwd} {workspace} {".into())
+        );
+        assert_eq!(fill_args("kitty {2}", &args), Err("2".into()));
+        assert_eq!(fill_args("nvim {arg:dir}", &args), Err("dir".into()));
+    }
+
+    #[test]
+    fn test_instance_key() {
+        let args = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+
+        let first = instance_key("notes", &args(&[("file", "a"), ("1", "b")]));
+        let same = instance_key("notes", &args(&[("1", "b"), ("file", "a")]));
+        let other = instance_key("notes", &args(&[("file", "b"), ("1", "b")]));
+
+        assert_eq!(first, same);
+        assert_ne!(first, other);
+        assert_eq!(first, "notes:1=b,file=a");
+        assert_eq!(
+            instance_workspace("notes:file=~/todo list.md"),
+            "notes_file___todo_list.md"
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        let cases = [
+            ("500ms", Some(Duration::from_millis(500))),
+            ("2s", Some(Duration::from_secs(2))),
+            (" 0.25 s", Some(Duration::from_millis(250))),
+            ("150", Some(Duration::from_millis(150))),
+            ("-1s", None),
+            ("soon", None),
+            ("", None),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(parse_duration(text), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_clamp_position() {
+        let monitor = (1920, 0, 1920, 1080);
+        let cases = [
+            ((2020, 100), (800, 600), (2020, 100)),
+            ((3500, 900), (800, 600), (3040, 480)),
+            ((-50, -20), (800, 600), (1920, 0)),
+            ((2000, 10), (2400, 1200), (1920, 0)),
+        ];
+
+        for (at, size, expected) in cases {
+            assert_eq!(clamp_position(monitor, at, size), expected);
+        }
+    }
+
+    #[test]
+    fn test_regex_match() {
+        let test_cases = [
+            ("db", "db-2", true),
+            ("^db$", "db-2", false),
+            ("^db.*", "db-2", true),
+            ("^web|^db", "db", true),
+            ("a*b$", "xb", true),
+            ("HDMI-.-1", "HDMI-A-1", true),
+            ("DP-1", "HDMI-A-1", false),
+            ("^(DP|HDMI)-[0-9]$", "DP-2", true),
+            ("^db(", "db", false),
+        ];
+
+        for (pattern, text, expected) in test_cases {
+            assert_eq!(regex_match(pattern, text), expected, "{pattern} {text}");
+        }
+
+        assert!(check_pattern("^dev-[0-9]+$"));
+        assert!(check_pattern("-98"));
+        assert!(!check_pattern("^db("));
+        assert!(!check_pattern("[a-"));
+
+        assert!(matches_location("3", "3", "db"));
+        assert!(!matches_location("3", "13", "db3"));
+        assert!(matches_location("-98", "-98", "special:test"));
+    }
 }